@@ -1,21 +1,29 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use futures::prelude::*;
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
+use warp::http::StatusCode;
 use warp::ws::Message;
-use warp::Filter;
+use warp::{Filter, Reply};
 
-use media_server::sdp::attributes::Candidate;
-use media_server::sdp::enums::{FingerprintHashFunction, IceCandidateType, IceTransportType, MediaType, RtpCodecName};
-use media_server::sdp::types::CertificateFingerprint;
-use media_server::sdp::webrtc::{RtpEncoding, RtpMediaDescription, UnifiedBundleSession};
+use media_server::sdp::attributes::{parse_attribute_bytes, BaseAttribute, Candidate, ParsableAttribute};
+use media_server::sdp::enums::{
+    FingerprintHashFunction, IceCandidateType, IceOption, IceTransportType, MediaType, RtpCodecName,
+};
+use media_server::sdp::types::{CertificateFingerprint, Mid, Ssrc};
+use media_server::sdp::webrtc::{
+    extension_uri, CodecCapability, Codecs, ExtensionCapability, Extensions, MediaDescription, MediaDirection,
+    RtpEncoding, RtpMediaDescription, UnifiedBundleSession,
+};
 use media_server::{
-    DtlsConnectionHash, LoggingLevel, MediaFrameType, Properties, RtpBundleTransport, RtpBundleTransportConnection,
-    RtpIncomingSourceGroup,
+    srtp_protection_profiles_property, DtlsConnectionHash, LoggingLevel, MediaFrameType, Properties, RtpBundleTransport,
+    RtpBundleTransportConnection, RtpIncomingSourceGroup, RtpOutgoingSourceGroup, RtpStreamTransponder,
+    SRTP_PROTECTION_PROFILES,
 };
 
 #[derive(Debug, Clone, StructOpt)]
@@ -26,6 +34,13 @@ struct Opts {
     public_ip: IpAddr,
     #[structopt(short = "r", long, parse(try_from_str = parse_port_range))]
     port_range: Option<(u16, u16)>,
+    /// Origin allowed to open the signaling websocket or fetch the static
+    /// demo assets, e.g. `--allowed-origin https://example.com`. May be
+    /// repeated. Requests with no `Origin` header (same-origin navigations,
+    /// curl, server-to-server) are always allowed through, matching how
+    /// browsers only send `Origin` on cross-origin/CORS-eligible requests.
+    #[structopt(long = "allowed-origin")]
+    allowed_origins: Vec<String>,
 }
 
 fn parse_port_range(s: &str) -> Result<(u16, u16), String> {
@@ -35,32 +50,197 @@ fn parse_port_range(s: &str) -> Result<(u16, u16), String> {
     Ok((min, max))
 }
 
+/// The signaling protocol version this server speaks. Bump this when a
+/// `C2SMessage`/`S2CMessage` change isn't backwards compatible; bump
+/// `MIN_PROTOCOL_VERSION` too if older clients can no longer be served at
+/// all.
+const PROTOCOL_VERSION: u32 = 1;
+const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Optional signaling features this server can enable when a client asks for
+/// them in its `Hello`. Unrecognized entries in a client's `supported` list
+/// are ignored rather than rejected, so new client-side features can be
+/// rolled out ahead of server support.
+const SUPPORTED_FEATURES: &[&str] = &["trickle-ice", "simulcast", "renegotiation"];
+
+/// `a=fingerprint` hash functions we'll answer with, strongest first. We pick
+/// the first one here that the offer also lists, rather than hard-coding
+/// SHA-256, so peers that only offer stricter crypto still connect.
+const PREFERRED_FINGERPRINT_HASHES: &[FingerprintHashFunction] = &[
+    FingerprintHashFunction::Sha512,
+    FingerprintHashFunction::Sha384,
+    FingerprintHashFunction::Sha256,
+    FingerprintHashFunction::Sha224,
+    FingerprintHashFunction::Sha1,
+];
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 enum C2SMessage {
+    /// Mandatory first message on every websocket connection, before any
+    /// `Offer`. `protocol_version` is the highest version the client
+    /// understands (it's assumed to also speak every version below it);
+    /// `supported` is the set of optional feature names it knows about.
+    Hello {
+        protocol_version: u32,
+        supported: Vec<String>,
+    },
     Offer {
         #[serde(with = "serde_with::rust::display_fromstr")]
         sdp: UnifiedBundleSession,
+        /// Id (from `sessions`) of an already-published session whose audio/video
+        /// should be forwarded into this one, turning this offer into a subscriber
+        /// rather than a publisher.
+        #[serde(default)]
+        subscribe_to: Option<String>,
     },
+    /// A single additional remote ICE candidate, gathered after the offer was
+    /// sent. Carries the `candidate:...` attribute line (everything an
+    /// `a=candidate` line would hold after the `a=`: foundation, component,
+    /// transport, priority, type, raddr/rport, tcp-type) so server-reflexive,
+    /// relayed, and ICE-TCP candidates can be added incrementally instead of
+    /// requiring the whole SDP up front. `mid` identifies which m-line the
+    /// candidate was gathered for; we only ever run one ICE transport per
+    /// session (max-bundle), so it's carried for protocol completeness and
+    /// logging rather than routing.
+    Candidate {
+        mid: String,
+        candidate: String,
+    },
+    /// Signals that the remote has finished gathering candidates; no further
+    /// `Candidate` messages will follow for this offer/answer exchange.
+    EndOfCandidates {},
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 enum S2CMessage {
+    /// Reply to [`C2SMessage::Hello`]: `protocol_version` is the version the
+    /// server will actually speak for the rest of this connection (the
+    /// highest mutually-supported one), and `enabled` is the subset of the
+    /// client's requested features the server will use.
+    Welcome {
+        protocol_version: u32,
+        enabled: Vec<String>,
+    },
     Answer {
         #[serde(with = "serde_with::rust::display_fromstr")]
         sdp: UnifiedBundleSession,
     },
+    /// A single additional local ICE candidate; see [`C2SMessage::Candidate`].
+    Candidate {
+        candidate: String,
+    },
+    /// See [`C2SMessage::EndOfCandidates`].
+    EndOfCandidates {},
+    /// Reports a signaling-level failure, so the client has something more
+    /// useful to act on than an abruptly closed socket. `code` is a stable,
+    /// machine-readable identifier (e.g. `"bad-sdp"`, `"unsupported-message"`);
+    /// `message` is a human-readable detail for logs/debugging. `fatal`
+    /// indicates the connection is being (or about to be) closed because of
+    /// this error, as opposed to a recoverable per-message problem.
+    Error {
+        code: String,
+        message: String,
+        fatal: bool,
+    },
+}
+
+/// Transport-agnostic carrier for the signaling message exchange.
+/// Implementations own how `C2SMessage`/`S2CMessage` values actually reach
+/// the remote (WebSocket text frames, long-poll HTTP, an in-process channel
+/// for tests, ...); [`SignalingSession`] only ever sees parsed messages, so
+/// the offer/answer/candidate state machine doesn't care which one it's
+/// running over.
+#[async_trait::async_trait]
+trait SignalingTransport: Send {
+    /// Waits for the next message from the remote. Returns `None` once the
+    /// remote is gone — the underlying transport closed, or (for transports
+    /// that frame/parse messages themselves) a framing error the transport
+    /// has already reported to the remote as an [`S2CMessage::Error`].
+    async fn recv(&mut self) -> Option<C2SMessage>;
+
+    /// Delivers `msg` to the remote. Swallows its own send failures, same as
+    /// this crate's error replies always have: by the time a send fails
+    /// there's nothing more useful to do than let the next `recv` report the
+    /// transport as gone.
+    async fn send(&mut self, msg: &S2CMessage);
+
+    /// Tears down the transport itself (not the media session it may have
+    /// negotiated).
+    async fn close(&mut self);
 }
 
-async fn send_message(websocket: &mut warp::ws::WebSocket, message: &S2CMessage) -> Result<(), Box<dyn Error>> {
-    let message = serde_json::to_string(message).unwrap();
+/// Logs and sends an [`S2CMessage::Error`]. Pulled out of
+/// [`SignalingTransport::send`] so every transport and every layer above it
+/// gets the same logging for free instead of repeating it.
+async fn send_error(transport: &mut impl SignalingTransport, code: &str, message: impl Into<String>, fatal: bool) {
+    let message = message.into();
+
+    log::warn!("signaling error ({}{}): {}", code, if fatal { ", fatal" } else { "" }, message);
+
+    transport
+        .send(&S2CMessage::Error {
+            code: code.to_owned(),
+            message,
+            fatal,
+        })
+        .await;
+}
+
+/// [`SignalingTransport`] backed by a `warp` WebSocket: each
+/// `C2SMessage`/`S2CMessage` is framed as a single JSON text frame.
+struct WarpWebSocketTransport(warp::ws::WebSocket);
+
+#[async_trait::async_trait]
+impl SignalingTransport for WarpWebSocketTransport {
+    async fn recv(&mut self) -> Option<C2SMessage> {
+        let message = match self.0.try_next().await {
+            Ok(Some(message)) => message,
+            Ok(None) => return None,
+            Err(e) => {
+                log::warn!("websocket error: {}", e);
+                return None;
+            }
+        };
 
-    log::info!("sending: {}", message);
+        if message.is_close() {
+            return None;
+        }
 
-    websocket.send(Message::text(message)).await?;
+        let text = match message.to_str() {
+            Ok(text) => text,
+            Err(()) => {
+                let reason = format!("unexpected message type in websocket: {:?}", message);
+                send_error(self, "unsupported-message", reason, true).await;
+                return None;
+            }
+        };
 
-    Ok(())
+        log::info!("message: {}", text);
+
+        match serde_json::from_str(text) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                send_error(self, "bad-message", format!("failed to parse message: {}", e), true).await;
+                None
+            }
+        }
+    }
+
+    async fn send(&mut self, msg: &S2CMessage) {
+        let text = serde_json::to_string(msg).unwrap();
+
+        log::info!("sending: {}", text);
+
+        if let Err(e) = self.0.send(Message::text(text)).await {
+            log::warn!("failed to send signaling message: {}", e);
+        }
+    }
+
+    async fn close(&mut self) {
+        let _ = self.0.close().await;
+    }
 }
 
 fn add_rtp_properties_from_media_description(properties: &mut Properties, media_description: &RtpMediaDescription) {
@@ -80,9 +260,9 @@ fn add_rtp_properties_from_media_description(properties: &mut Properties, media_
         media_description.payloads.len() as i32,
     );
 
-    for (i, (uri, id)) in media_description.extensions.iter().enumerate() {
-        properties.set_int(&format!("{}.ext.{}.id", kind, i), *id as i32);
-        properties.set_string(&format!("{}.ext.{}.uri", kind, i), uri);
+    for (i, extension) in media_description.extensions.iter().enumerate() {
+        properties.set_int(&format!("{}.ext.{}.id", kind, i), extension.id as i32);
+        properties.set_string(&format!("{}.ext.{}.uri", kind, i), &extension.uri);
     }
 
     properties.set_int(
@@ -94,13 +274,21 @@ fn add_rtp_properties_from_media_description(properties: &mut Properties, media_
 fn get_rtp_properties_from_sdp(sdp: &UnifiedBundleSession) -> Properties {
     let mut properties = Properties::new();
 
-    let first_audio_media = sdp.media_descriptions.iter().find(|md| md.kind == MediaType::Audio);
+    let first_audio_media = sdp
+        .media_descriptions
+        .iter()
+        .filter_map(MediaDescription::as_rtp)
+        .find(|md| md.kind == MediaType::Audio);
 
     if let Some(media_description) = first_audio_media {
         add_rtp_properties_from_media_description(&mut properties, media_description);
     }
 
-    let first_video_media = sdp.media_descriptions.iter().find(|md| md.kind == MediaType::Video);
+    let first_video_media = sdp
+        .media_descriptions
+        .iter()
+        .filter_map(MediaDescription::as_rtp)
+        .find(|md| md.kind == MediaType::Video);
 
     if let Some(media_description) = first_video_media {
         add_rtp_properties_from_media_description(&mut properties, media_description);
@@ -109,78 +297,383 @@ fn get_rtp_properties_from_sdp(sdp: &UnifiedBundleSession) -> Properties {
     properties
 }
 
-/// Filters the codecs, rtcp feedbacks, and extensions in the SDP according to
-/// the media-server capabilities.
-fn filter_answer_to_capabilities(sdp: &mut UnifiedBundleSession) {
-    for media_description in &mut sdp.media_descriptions {
-        let kind = media_description.kind.clone();
-
-        media_description.payloads.retain(|payload| match kind {
-            MediaType::Audio => match payload.name {
-                RtpCodecName::Opus => true,
-                RtpCodecName::Pcmu => true,
-                RtpCodecName::Pcma => true,
-                _ => false,
-            },
-            MediaType::Video => match payload.name {
-                RtpCodecName::Vp8 => true,
-                RtpCodecName::Vp9 => true,
-                RtpCodecName::H264 => match payload.parameters.get("packetization-mode") {
-                    Some(mode) => mode == "1",
-                    None => false,
-                },
-                _ => false,
-            },
-            _ => false,
-        });
+/// The codecs this server can actually handle, consulted by
+/// [`UnifiedBundleSession::answer`] to negotiate each media description's
+/// payloads down from whatever the offerer listed.
+fn supported_codecs() -> Codecs {
+    let video_feedback = |codec: CodecCapability| {
+        codec
+            .support_feedback("goog-remb", None::<String>)
+            .support_feedback("transport-cc", None::<String>)
+            .support_feedback("ccm", Some("fir"))
+            .support_feedback("nack", None::<String>)
+            .support_feedback("nack", Some("pli"))
+    };
+
+    Codecs::new()
+        .add(CodecCapability::new(RtpCodecName::Opus, 48000, Some(2)))
+        .add(CodecCapability::new(RtpCodecName::Pcmu, 8000, None))
+        .add(CodecCapability::new(RtpCodecName::Pcma, 8000, None))
+        .add(video_feedback(CodecCapability::new(RtpCodecName::Vp8, 90000, None)).with_rtx())
+        .add(video_feedback(CodecCapability::new(RtpCodecName::Vp9, 90000, None)).with_rtx())
+        .add(
+            video_feedback(CodecCapability::new(RtpCodecName::H264, 90000, None))
+                .require_parameter("packetization-mode", "1")
+                .with_rtx(),
+        )
+}
 
-        for payload in &mut media_description.payloads {
-            payload.supported_feedback.retain(|id, param| match kind {
-                MediaType::Video => match (id.as_str(), param.as_deref()) {
-                    ("goog-remb", None) => true,
-                    ("transport-cc", None) => true,
-                    ("ccm", Some("fir")) => true,
-                    ("nack", None) => true,
-                    ("nack", Some("pli")) => true,
-                    _ => false,
-                },
-                _ => false,
-            });
-        }
+/// The header extensions this server actually understands, consulted by
+/// [`UnifiedBundleSession::answer`] to negotiate each media description's
+/// extensions down from whatever the offerer listed. Audio- and
+/// video-specific extensions are both registered here since `answer`
+/// negotiates per media description, not per session; an audio-only
+/// extension simply never shows up in a video offer's extension list.
+fn supported_extensions() -> Extensions {
+    Extensions::new()
+        .add(ExtensionCapability::new("urn:ietf:params:rtp-hdrext:ssrc-audio-level"))
+        .add(ExtensionCapability::new("urn:3gpp:video-orientation"))
+        .add(ExtensionCapability::new(extension_uri::TRANSPORT_WIDE_CC))
+        .add(ExtensionCapability::new(extension_uri::MID))
+        .add(ExtensionCapability::new(extension_uri::RTP_STREAM_ID))
+        .add(ExtensionCapability::new(extension_uri::REPAIRED_RTP_STREAM_ID))
+        .add(ExtensionCapability::new(extension_uri::ABS_SEND_TIME))
+}
 
-        media_description.extensions.retain(|uri, _id| match kind {
-            MediaType::Audio => match uri.as_str() {
-                "urn:ietf:params:rtp-hdrext:ssrc-audio-level" => true,
-                "urn:ietf:params:rtp-hdrext:sdes:mid" => true,
-                "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id" => true,
-                "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time" => true,
-                _ => false,
-            },
-            MediaType::Video => match uri.as_str() {
-                "urn:3gpp:video-orientation" => true,
-                "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01" => true,
-                "urn:ietf:params:rtp-hdrext:sdes:mid" => true,
-                "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id" => true,
-                "urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id" => true,
-                "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time" => true,
-                _ => false,
-            },
-            _ => false,
-        });
-    }
+/// One track a publisher is sending, kept around so a later subscriber's
+/// session can attach it as the source of an [`RtpStreamTransponder`].
+/// `template` is the answer-side [`RtpMediaDescription`] we already
+/// negotiated for it, reused (with a fresh mid/SSRCs) to describe the same
+/// track to a subscriber. `incoming` is shared (`Arc<Mutex<..>>`) rather than
+/// owned outright, since a subscriber's [`ForwardedTrack`] holds a clone of it
+/// too: a transponder forwarding from this group must keep it alive even
+/// after the publisher's own `ActiveSession` (and thus this `PublishedTrack`)
+/// is torn down, or it'd be forwarding from a freed native object.
+#[allow(dead_code)]
+struct PublishedTrack {
+    kind: MediaFrameType,
+    template: RtpMediaDescription,
+    incoming: Arc<Mutex<RtpIncomingSourceGroup>>,
+}
+
+/// One track being forwarded into this connection from a publisher's
+/// [`PublishedTrack`]. Kept alive for as long as the subscriber's
+/// `ActiveSession` lives; dropping it tears down the forwarding. `_incoming`
+/// is the publisher's shared source group, held here only to keep it alive
+/// for as long as `transponder` is still forwarding from it.
+#[allow(dead_code)]
+struct ForwardedTrack {
+    outgoing: RtpOutgoingSourceGroup,
+    transponder: RtpStreamTransponder,
+    _incoming: Arc<Mutex<RtpIncomingSourceGroup>>,
 }
 
 #[allow(dead_code)]
 struct ActiveSession {
     transport: RtpBundleTransport,
     connection: RtpBundleTransportConnection,
-    incoming_source_groups: Vec<RtpIncomingSourceGroup>,
+    published: Vec<PublishedTrack>,
+    forwarded: Vec<ForwardedTrack>,
+}
+
+/// Sessions that were negotiated over either signalling path, keyed by an
+/// opaque id. The WHIP resource URL (`/whip/<id>`) and the websocket's own
+/// bookkeeping both resolve through this map so teardown works the same way
+/// regardless of which protocol created the session, and so a subscriber's
+/// offer can look up a publisher's [`ActiveSession::published`] tracks to
+/// forward by id.
+type Sessions = Arc<Mutex<HashMap<String, ActiveSession>>>;
+
+fn generate_session_id() -> String {
+    use rand::distributions::Alphanumeric;
+    rand::thread_rng().sample_iter(Alphanumeric).take(16).collect()
+}
+
+/// A signalling transport: something that can hand `handle_offer` a remote
+/// offer and deliver the resulting answer back to that same remote, without
+/// `handle_offer` needing to know whether it's talking to a WebSocket, an
+/// HTTP request, or anything else. Implement this to add a new signalling
+/// backend (e.g. a Janus room, LiveKit) without touching the transport and
+/// codec-negotiation logic below.
+#[async_trait::async_trait]
+trait Signaller: Send {
+    /// Waits for the remote's SDP offer.
+    async fn recv_offer(&mut self) -> Result<UnifiedBundleSession, Box<dyn Error>>;
+
+    /// Delivers the locally negotiated answer to the remote.
+    async fn send_answer(&mut self, answer: &UnifiedBundleSession) -> Result<(), Box<dyn Error>>;
+
+    /// Notifies the remote of an additional local ICE candidate gathered
+    /// after the initial answer was sent.
+    // TODO: Not called yet to push additional candidates - candidate gathering
+    //       in `handle_offer` is still synchronous and folded into the initial
+    //       answer, so there's nothing further to trickle. Wire this up once
+    //       gathering is async.
+    async fn on_ice_candidate(&mut self, candidate: &Candidate) -> Result<(), Box<dyn Error>>;
+
+    /// Notifies the remote that local candidate gathering has finished; no
+    /// further [`Signaller::on_ice_candidate`] calls will follow. Defaults to
+    /// a no-op for signalling backends with no way to trickle this (e.g. WHIP,
+    /// whose answer SDP must already be complete).
+    async fn on_end_of_candidates(&mut self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Tears down the signalling channel itself (not the media session).
+    async fn close(&mut self);
+
+    /// Id (from `sessions`) of a published session whose tracks the remote
+    /// wants forwarded into this one. Only meaningful once `recv_offer` has
+    /// returned; defaults to `None` for signallers that don't support it.
+    fn subscribe_to(&self) -> Option<&str> {
+        None
+    }
+
+    /// Optional signaling features negotiated with the remote ahead of time
+    /// (e.g. over a [`C2SMessage::Hello`]/[`S2CMessage::Welcome`] handshake),
+    /// so [`handle_offer`] can branch on what it's allowed to use. Defaults
+    /// to empty for signallers with no equivalent handshake (e.g. WHIP).
+    fn negotiated_features(&self) -> &[String] {
+        &[]
+    }
+}
+
+/// Outcome of the `Hello`/`Welcome` exchange that must open every signaling
+/// session before any SDP is processed.
+#[derive(Debug, Clone)]
+struct NegotiatedProtocol {
+    version: u32,
+    enabled_features: Vec<String>,
+}
+
+/// Performs the mandatory `Hello`/`Welcome` exchange. Returns an error
+/// (without having sent a `Welcome`) if the remote's message isn't a `Hello`,
+/// or if its supported protocol versions (it's assumed to speak every
+/// version from 1 up to the one it reports) don't overlap
+/// `MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION`.
+async fn negotiate_protocol(transport: &mut impl SignalingTransport) -> Result<NegotiatedProtocol, Box<dyn Error>> {
+    let message = match transport.recv().await {
+        Some(message) => message,
+        None => return Err("transport closed before a hello was received".into()),
+    };
+
+    let (client_protocol_version, supported) = match message {
+        C2SMessage::Hello {
+            protocol_version,
+            supported,
+        } => (protocol_version, supported),
+        other => {
+            let reason = format!("expected a hello as the first message, got {:?}", other);
+            send_error(transport, "unsupported-message", &reason, true).await;
+            return Err(reason.into());
+        }
+    };
+
+    let negotiated_version = client_protocol_version.min(PROTOCOL_VERSION);
+
+    if negotiated_version < MIN_PROTOCOL_VERSION {
+        let reason = format!(
+            "client's supported protocol versions (up to {}) don't overlap this server's ({}..={})",
+            client_protocol_version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+        );
+        send_error(transport, "unsupported-protocol-version", &reason, true).await;
+        return Err(reason.into());
+    }
+
+    let enabled_features: Vec<String> = SUPPORTED_FEATURES
+        .iter()
+        .filter(|feature| supported.iter().any(|s| s == *feature))
+        .map(|&feature| feature.to_owned())
+        .collect();
+
+    transport
+        .send(&S2CMessage::Welcome {
+            protocol_version: negotiated_version,
+            enabled: enabled_features.clone(),
+        })
+        .await;
+
+    Ok(NegotiatedProtocol {
+        version: negotiated_version,
+        enabled_features,
+    })
+}
+
+/// Drives the offer/answer/candidate side of [`Signaller`] over any
+/// [`SignalingTransport`], so the same logic runs whether the remote is
+/// talking over a WebSocket or something else entirely.
+struct TransportSignaller<'a, T: SignalingTransport> {
+    transport: &'a mut T,
+    sessions: Sessions,
+    /// Id (from `sessions`) of the session this transport already negotiated,
+    /// if any, so a trickled [`C2SMessage::Candidate`] arriving before the
+    /// next offer can be routed to its connection.
+    session_id: Option<String>,
+    negotiated: NegotiatedProtocol,
+    subscribe_to: Option<String>,
+}
+
+impl<'a, T: SignalingTransport> TransportSignaller<'a, T> {
+    /// Parses a trickled `a=candidate:...` line and applies it to the
+    /// existing session's connection, if there is one. Malformed lines and
+    /// candidates arriving before any session exists are logged and dropped
+    /// rather than treated as a signalling error.
+    fn apply_remote_candidate(&self, line: &str) {
+        let session_id = match &self.session_id {
+            Some(session_id) => session_id,
+            None => {
+                log::warn!("ignoring trickled ICE candidate received before any offer: {}", line);
+                return;
+            }
+        };
+
+        let attribute = match parse_attribute_bytes(format!("a={}", line).as_bytes()) {
+            Ok((_, attribute, _)) => attribute,
+            Err(e) => {
+                log::warn!("failed to parse trickled ICE candidate {:?}: {}", line, e);
+                return;
+            }
+        };
+
+        let candidate = match attribute.as_any().downcast_ref::<Candidate>() {
+            Some(candidate) => candidate.clone(),
+            None => {
+                log::warn!("trickled ICE candidate line did not parse as a=candidate: {}", line);
+                return;
+            }
+        };
+
+        let mut sessions = self.sessions.lock().unwrap();
+
+        let session = match sessions.get_mut(session_id) {
+            Some(session) => session,
+            None => return,
+        };
+
+        session.connection.add_remote_candidate(
+            &candidate.address,
+            candidate.port,
+            &candidate.transport,
+            &candidate.kind,
+            candidate.priority,
+            candidate.rel_addr.as_deref(),
+            candidate.rel_port,
+        );
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a, T: SignalingTransport> Signaller for TransportSignaller<'a, T> {
+    async fn recv_offer(&mut self) -> Result<UnifiedBundleSession, Box<dyn Error>> {
+        loop {
+            let message = match self.transport.recv().await {
+                Some(message) => message,
+                None => return Err("transport closed before an offer was received".into()),
+            };
+
+            match message {
+                C2SMessage::Offer { sdp, subscribe_to } => {
+                    self.subscribe_to = subscribe_to;
+                    return Ok(sdp);
+                }
+                C2SMessage::Candidate { mid, candidate } => {
+                    log::debug!("trickled ICE candidate for mid {:?}: {}", mid, candidate);
+                    self.apply_remote_candidate(&candidate);
+                }
+                C2SMessage::EndOfCandidates {} => {}
+                C2SMessage::Hello { .. } => {
+                    let reason = "hello is only valid as the first message on a connection";
+                    send_error(self.transport, "unsupported-message", reason, true).await;
+                    return Err(reason.into());
+                }
+            }
+        }
+    }
+
+    async fn send_answer(&mut self, answer: &UnifiedBundleSession) -> Result<(), Box<dyn Error>> {
+        self.transport.send(&S2CMessage::Answer { sdp: answer.clone() }).await;
+        Ok(())
+    }
+
+    async fn on_ice_candidate(&mut self, candidate: &Candidate) -> Result<(), Box<dyn Error>> {
+        let candidate = candidate
+            .to_string()
+            .map(|value| format!("candidate:{}", value))
+            .ok_or("candidate attribute unexpectedly has no value")?;
+
+        self.transport.send(&S2CMessage::Candidate { candidate }).await;
+        Ok(())
+    }
+
+    async fn on_end_of_candidates(&mut self) -> Result<(), Box<dyn Error>> {
+        self.transport.send(&S2CMessage::EndOfCandidates {}).await;
+        Ok(())
+    }
+
+    async fn close(&mut self) {
+        self.transport.close().await;
+    }
+
+    fn subscribe_to(&self) -> Option<&str> {
+        self.subscribe_to.as_deref()
+    }
+
+    fn negotiated_features(&self) -> &[String] {
+        &self.negotiated.enabled_features
+    }
+}
+
+/// Drives a single WHIP `POST /whip` request: the offer is already in hand
+/// from the request body, and `send_answer` just stashes the answer for the
+/// handler to write back as the HTTP response.
+struct WhipSignaller {
+    offer: Option<UnifiedBundleSession>,
+    answer: Option<UnifiedBundleSession>,
+    subscribe_to: Option<String>,
+}
+
+impl WhipSignaller {
+    fn new(offer: UnifiedBundleSession, subscribe_to: Option<String>) -> Self {
+        WhipSignaller {
+            offer: Some(offer),
+            answer: None,
+            subscribe_to,
+        }
+    }
+
+    fn into_answer(self) -> Option<UnifiedBundleSession> {
+        self.answer
+    }
+}
+
+#[async_trait::async_trait]
+impl Signaller for WhipSignaller {
+    async fn recv_offer(&mut self) -> Result<UnifiedBundleSession, Box<dyn Error>> {
+        self.offer.take().ok_or_else(|| "WHIP signaller only accepts a single offer".into())
+    }
+
+    async fn send_answer(&mut self, answer: &UnifiedBundleSession) -> Result<(), Box<dyn Error>> {
+        self.answer = Some(answer.clone());
+        Ok(())
+    }
+
+    async fn on_ice_candidate(&mut self, _candidate: &Candidate) -> Result<(), Box<dyn Error>> {
+        // TODO: WHIP (RFC 9725) trickles additional candidates via a PATCH on the
+        //       resource URL, which isn't implemented yet.
+        Ok(())
+    }
+
+    async fn close(&mut self) {}
+
+    fn subscribe_to(&self) -> Option<&str> {
+        self.subscribe_to.as_deref()
+    }
 }
 
 async fn handle_offer(
     opts: Arc<Opts>,
-    websocket: &mut warp::ws::WebSocket,
-    offer: &UnifiedBundleSession,
+    sessions: &Sessions,
+    signaller: &mut dyn Signaller,
 ) -> Result<ActiveSession, Box<dyn Error>> {
     // TODO: We want to implement something along the lines of the
     //       media-server-node manual signalling example in here.
@@ -190,17 +683,24 @@ async fn handle_offer(
     //       so we're just gonna use the raw native API to get something running here,
     //       and use it to guide implementation of those APIs later.
 
+    let offer = signaller.recv_offer().await?;
+    let subscribe_to = signaller.subscribe_to().map(str::to_owned);
+
+    log::debug!("negotiated signaling features for this offer: {:?}", signaller.negotiated_features());
+
     // TODO: We shouldn't be creating one RtpBundleTransport (Endpoint) per connection.
     let transport = RtpBundleTransport::new(None)?;
 
     // This will generate a new ice ufrag/pwd,
     // we need to add our ICE candidates and DTLS fingerprint.
-    let mut answer = offer.answer();
-
-    filter_answer_to_capabilities(&mut answer);
+    let mut answer = offer.answer(&supported_codecs(), &supported_extensions());
 
     answer.ice_lite = true;
 
+    if signaller.negotiated_features().iter().any(|feature| feature == "trickle-ice") {
+        answer.ice_options.insert(IceOption::Trickle);
+    }
+
     answer.candidates.push(Candidate {
         foundation: "1".to_owned(),
         component: 1,
@@ -215,17 +715,17 @@ async fn handle_offer(
         tcp_type: None,
     });
 
-    // TODO: media_server::get_certificate_fingerprint should probably return these in the right type already
-    let our_fingerprint = media_server::get_certificate_fingerprint(DtlsConnectionHash::Sha256)?;
-    answer.fingerprints.append(
-        FingerprintHashFunction::Sha256,
-        CertificateFingerprint::from_str(&our_fingerprint)?,
-    );
+    let offer_fingerprint = PREFERRED_FINGERPRINT_HASHES
+        .iter()
+        .find_map(|hash| offer.fingerprints.iter().find(|fingerprint| fingerprint.hash_function == *hash))
+        .ok_or("offer has no a=fingerprint in a hash function we support")?;
+    let local_hash = DtlsConnectionHash::from(&offer_fingerprint.hash_function);
 
-    let offer_fingerprint = offer
+    // TODO: media_server::get_certificate_fingerprint should probably return these in the right type already
+    let our_fingerprint = media_server::get_certificate_fingerprint(local_hash)?;
+    answer
         .fingerprints
-        .get(&FingerprintHashFunction::Sha256)
-        .ok_or("sha-256 dtls fingerprint missing from offer")?;
+        .push(CertificateFingerprint::from_hex_digest(offer_fingerprint.hash_function.clone(), &our_fingerprint)?);
 
     let properties = Properties::new();
     properties.set_string("ice.localUsername", &answer.ice_ufrag);
@@ -233,21 +733,21 @@ async fn handle_offer(
     properties.set_string("ice.remoteUsername", &offer.ice_ufrag);
     properties.set_string("ice.remotePassword", &offer.ice_pwd);
     properties.set_string("dtls.setup", offer.setup_role.as_ref());
-    properties.set_string("dtls.hash", "SHA-256");
-    properties.set_string("dtls.fingerprint", &offer_fingerprint.to_string());
+    properties.set_string("dtls.hash", local_hash.property_name());
+    properties.set_string("dtls.fingerprint", &offer_fingerprint.digest_hex());
     properties.set_bool("disableSTUNKeepAlive", false);
-    properties.set_string("srtpProtectionProfiles", "");
+    properties.set_string("srtpProtectionProfiles", &srtp_protection_profiles_property(SRTP_PROTECTION_PROFILES));
 
     let username = answer.ice_ufrag.clone() + ":" + &offer.ice_ufrag;
     let connection = transport.add_ice_transport(username.as_str(), &properties)?;
 
-    let remote_properties = get_rtp_properties_from_sdp(offer);
+    let remote_properties = get_rtp_properties_from_sdp(&offer);
     connection.set_remote_properties(&remote_properties);
 
     let local_properties = get_rtp_properties_from_sdp(&answer);
     connection.set_local_properties(&local_properties);
 
-    let mut incoming_source_groups = Vec::new();
+    let mut published = Vec::new();
 
     // TODO: We've got a weird bug here where media-server isn't matching up the RTX
     //       packets with an encoding - both the MID and RID headers seems to be missing.
@@ -257,15 +757,23 @@ async fn handle_offer(
     //       encoding is not currently active. Doesn't look like there is anything to do
     //       and it recovers happily once all of the encodings become active.
 
-    for media_description in &offer.media_descriptions {
+    for media_description in offer.media_descriptions.iter().filter_map(MediaDescription::as_rtp) {
         let frame_type = match media_description.kind {
             MediaType::Audio => MediaFrameType::Audio,
             MediaType::Video => MediaFrameType::Video,
             _ => continue,
         };
 
+        let template = answer
+            .media_descriptions
+            .iter()
+            .filter_map(MediaDescription::as_rtp)
+            .find(|answer_media| answer_media.mid == media_description.mid)
+            .cloned()
+            .ok_or("answer is missing the media description we just negotiated")?;
+
         for encoding in &media_description.encodings {
-            let incoming_source_group = match encoding {
+            let incoming = match encoding {
                 RtpEncoding::Rid { rid, .. } => connection.add_incoming_source_group(
                     frame_type,
                     Some(&media_description.mid.0),
@@ -282,63 +790,299 @@ async fn handle_offer(
                 )?,
             };
 
-            incoming_source_groups.push(incoming_source_group);
+            published.push(PublishedTrack {
+                kind: frame_type,
+                template: template.clone(),
+                incoming: Arc::new(Mutex::new(incoming)),
+            });
+        }
+    }
+
+    // Mirror another session's published tracks into this one, turning this offer
+    // into a subscriber: each becomes a `sendonly` m= section on fresh SSRCs, fed
+    // by a transponder attached to the publisher's incoming source group.
+    let mut forwarded = Vec::new();
+
+    if let Some(publisher_id) = subscribe_to {
+        let mut registry = sessions.lock().unwrap();
+
+        let publisher = registry
+            .get_mut(&publisher_id)
+            .ok_or_else(|| format!("no published session with id {:?}", publisher_id))?;
+
+        for (index, track) in publisher.published.iter_mut().enumerate() {
+            let media_ssrc: u32 = rand::random();
+            let rtx_ssrc: u32 = rand::random();
+
+            let mut outgoing =
+                connection.add_outgoing_source_group(track.kind, Some(&track.template.mid.0), media_ssrc, Some(rtx_ssrc))?;
+            let mut transponder = outgoing.add_transponder();
+            transponder.set_incoming(&mut track.incoming.lock().unwrap());
+
+            let mut media_description = track.template.clone();
+            media_description.mid = Mid::from(format!("sub{}", index).as_str());
+            media_description.direction = MediaDirection::SendOnly;
+            media_description.encodings = vec![RtpEncoding::SendingSsrc {
+                cname: answer.ice_ufrag.clone(),
+                ssrc: Ssrc(media_ssrc),
+                rtx_ssrc: Some(Ssrc(rtx_ssrc)),
+                fec_ssrc: None,
+            }];
+
+            answer.media_descriptions.push(MediaDescription::Rtp(media_description));
+            forwarded.push(ForwardedTrack {
+                outgoing,
+                transponder,
+                _incoming: track.incoming.clone(),
+            });
         }
     }
 
-    // TODO: Mirror back the tracks?
+    signaller.send_answer(&answer).await?;
 
-    send_message(websocket, &S2CMessage::Answer { sdp: answer }).await?;
+    // Candidate gathering above is synchronous and already folded into
+    // `answer`, so there's nothing left to trickle.
+    signaller.on_end_of_candidates().await?;
 
     Ok(ActiveSession {
         transport,
         connection,
-        incoming_source_groups,
+        published,
+        forwarded,
     })
 }
 
-async fn on_websocket_upgrade(opts: Arc<Opts>, mut websocket: warp::ws::WebSocket) {
-    // Stores the media-server objects for the current websocket
-    let mut session = None;
+/// Logs connection-wide and per-track quality counters for `session`, e.g.
+/// right before it's torn down, so operators have something to go on besides
+/// "the call dropped" when debugging a session after the fact.
+fn log_session_stats(id: &str, session: &ActiveSession) {
+    let stats = session.connection.get_stats();
+    log::info!(
+        "session {} connection stats: {:?} ice_connected={} rtt={:.1}ms",
+        id,
+        stats.dtls_state,
+        stats.ice_connected,
+        stats.round_trip_time_ms,
+    );
 
-    while let Ok(Some(message)) = websocket.try_next().await {
-        if message.is_close() {
-            log::info!("client closed websocket");
-            let _ = websocket.close().await;
-            return;
-        }
+    for track in &session.published {
+        let stats = track.incoming.lock().unwrap().get_stats();
+        log::info!(
+            "session {} {:?} track stats: {} packets / {} bytes received, {} lost, {:.1}ms jitter, {}bps, ssrc={} rid={:?}",
+            id,
+            track.kind,
+            stats.packets_received,
+            stats.bytes_received,
+            stats.packets_lost,
+            stats.jitter_ms,
+            stats.bitrate_bps,
+            stats.last_ssrc,
+            stats.last_rid,
+        );
+    }
+}
 
-        let text = match message.to_str() {
-            Ok(text) => text,
-            Err(()) => {
-                log::warn!("unexpected message type in websocket: {:?}", message);
-                return;
-            }
-        };
+/// Drives the full signaling lifecycle — the mandatory Hello/Welcome
+/// handshake followed by a loop of offer/answer negotiations — over any
+/// [`SignalingTransport`]. This is what lets [`handle_offer`] and
+/// [`TransportSignaller`] run unmodified over alternate carriers (long-poll
+/// HTTP, an in-process channel for tests, ...), not just a `warp` WebSocket.
+struct SignalingSession<T: SignalingTransport> {
+    transport: T,
+    opts: Arc<Opts>,
+    sessions: Sessions,
+}
 
-        log::info!("message: {}", text);
+impl<T: SignalingTransport> SignalingSession<T> {
+    fn new(transport: T, opts: Arc<Opts>, sessions: Sessions) -> Self {
+        Self { transport, opts, sessions }
+    }
 
-        let parsed: C2SMessage = match serde_json::from_str(text) {
-            Ok(parsed) => parsed,
+    async fn run(mut self) {
+        let negotiated = match negotiate_protocol(&mut self.transport).await {
+            Ok(negotiated) => negotiated,
             Err(e) => {
-                log::warn!("failed to parse websocket message: {}", e);
+                log::warn!("failed to negotiate signaling protocol: {}", e);
+                self.transport.close().await;
                 return;
             }
         };
 
-        log::info!("parsed: {:?}", parsed);
-
-        match parsed {
-            C2SMessage::Offer { sdp } => {
-                match handle_offer(opts.clone(), &mut websocket, &sdp).await {
-                    Ok(new_session) => session.replace(new_session),
-                    Err(e) => {
-                        log::warn!("failed to handle offer: {}", e);
-                        return;
-                    }
-                };
+        log::info!(
+            "negotiated signaling protocol v{} with features {:?}",
+            negotiated.version,
+            negotiated.enabled_features,
+        );
+
+        // Id of this session in the shared `sessions` map, if any offer has been handled yet.
+        let mut session_id = None;
+
+        loop {
+            let mut signaller = TransportSignaller {
+                transport: &mut self.transport,
+                sessions: self.sessions.clone(),
+                session_id: session_id.clone(),
+                negotiated: negotiated.clone(),
+                subscribe_to: None,
+            };
+
+            let new_session = match handle_offer(self.opts.clone(), &self.sessions, &mut signaller).await {
+                Ok(new_session) => new_session,
+                Err(e) => {
+                    log::warn!("failed to handle offer: {}", e);
+                    drop(signaller);
+                    send_error(&mut self.transport, "bad-sdp", e.to_string(), true).await;
+                    break;
+                }
+            };
+
+            let id = generate_session_id();
+            self.sessions.lock().unwrap().insert(id.clone(), new_session);
+
+            if let Some(old_id) = session_id.replace(id) {
+                if let Some(session) = self.sessions.lock().unwrap().remove(&old_id) {
+                    log_session_stats(&old_id, &session);
+                }
             }
-        };
+        }
+
+        self.transport.close().await;
+
+        if let Some(id) = session_id {
+            if let Some(session) = self.sessions.lock().unwrap().remove(&id) {
+                log_session_stats(&id, &session);
+            }
+        }
+    }
+}
+
+async fn on_websocket_upgrade(opts: Arc<Opts>, sessions: Sessions, websocket: warp::ws::WebSocket) {
+    SignalingSession::new(WarpWebSocketTransport(websocket), opts, sessions).run().await;
+}
+
+/// Query parameters accepted by `POST /whip`.
+#[derive(Deserialize)]
+struct WhipPublishQuery {
+    /// Id (from `sessions`) of an already-published session to forward into this one.
+    subscribe_to: Option<String>,
+}
+
+/// `POST /whip` — negotiates a new session from a bare SDP offer per the
+/// WHIP spec (RFC 9725), and registers it in `sessions` under a fresh
+/// resource id returned as the `Location` header.
+async fn whip_publish(
+    opts: Arc<Opts>,
+    sessions: Sessions,
+    query: WhipPublishQuery,
+    body: bytes::Bytes,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let sdp = match std::str::from_utf8(&body) {
+        Ok(sdp) => sdp,
+        Err(_) => return Ok(whip_error(StatusCode::BAD_REQUEST, "offer body is not valid utf-8")),
+    };
+
+    let offer = match UnifiedBundleSession::from_str(sdp) {
+        Ok(offer) => offer,
+        Err(e) => return Ok(whip_error(StatusCode::BAD_REQUEST, &format!("failed to parse offer: {}", e))),
+    };
+
+    let mut signaller = WhipSignaller::new(offer, query.subscribe_to);
+
+    let session = match handle_offer(opts, &sessions, &mut signaller).await {
+        Ok(session) => session,
+        Err(e) => {
+            log::warn!("failed to handle WHIP offer: {}", e);
+            return Ok(whip_error(StatusCode::INTERNAL_SERVER_ERROR, "failed to negotiate session"));
+        }
+    };
+
+    let answer = match signaller.into_answer() {
+        Some(answer) => answer,
+        None => return Ok(whip_error(StatusCode::INTERNAL_SERVER_ERROR, "negotiation completed without an answer")),
+    };
+
+    let id = generate_session_id();
+    sessions.lock().unwrap().insert(id.clone(), session);
+
+    let reply = warp::reply::with_status(answer.to_string(), StatusCode::CREATED);
+    let reply = warp::reply::with_header(reply, "Content-Type", "application/sdp");
+    let reply = warp::reply::with_header(reply, "Location", format!("/whip/{}", id));
+
+    Ok(reply.into_response())
+}
+
+/// `DELETE /whip/<id>` — tears down the session created by the matching
+/// `POST /whip`, identified by the resource id handed back in its
+/// `Location` header.
+async fn whip_teardown(id: String, sessions: Sessions) -> Result<impl warp::Reply, warp::Rejection> {
+    let status = match sessions.lock().unwrap().remove(&id) {
+        Some(session) => {
+            log_session_stats(&id, &session);
+            StatusCode::NO_CONTENT
+        }
+        None => StatusCode::NOT_FOUND,
+    };
+
+    Ok(warp::reply::with_status(warp::reply(), status))
+}
+
+fn whip_error(status: StatusCode, message: &str) -> warp::reply::Response {
+    warp::reply::with_status(message.to_owned(), status).into_response()
+}
+
+/// Rejection raised when a request carries an `Origin` header that isn't in
+/// `Opts::allowed_origins`.
+#[derive(Debug)]
+struct OriginNotAllowed;
+
+impl warp::reject::Reject for OriginNotAllowed {}
+
+/// Validates the `Origin` header (if present) against `opts.allowed_origins`,
+/// rejecting with [`OriginNotAllowed`] on a mismatch. Requests with no
+/// `Origin` header are passed through unchecked; the browser only sends one
+/// on cross-origin/CORS-eligible requests in the first place, so its absence
+/// isn't itself a signal of anything to block.
+fn check_origin(opts: Arc<Opts>) -> impl Filter<Extract = (Option<String>,), Error = warp::Rejection> + Clone {
+    warp::header::optional::<String>("origin").and_then(move |origin: Option<String>| {
+        let opts = opts.clone();
+        async move {
+            match &origin {
+                Some(origin) if !opts.allowed_origins.iter().any(|allowed| allowed == origin) => {
+                    Err(warp::reject::custom(OriginNotAllowed))
+                }
+                _ => Ok(origin),
+            }
+        }
+    })
+}
+
+/// Echoes a validated request origin back as `Access-Control-Allow-Origin`,
+/// so the response stays usable from credentialed cross-origin requests.
+/// Never emits a wildcard, and never more than the single matching origin.
+fn with_cors_header(reply: impl Reply, origin: Option<String>) -> warp::reply::Response {
+    match origin {
+        Some(origin) => warp::reply::with_header(reply, "Access-Control-Allow-Origin", origin).into_response(),
+        None => reply.into_response(),
+    }
+}
+
+/// `OPTIONS *` — answers CORS preflight requests for the `/ws` upgrade and
+/// the static asset routes. An allowed origin gets its preflight headers
+/// back; anything else is rejected the same way the real request would be.
+async fn handle_preflight(origin: Option<String>) -> Result<impl warp::Reply, std::convert::Infallible> {
+    let reply = warp::reply::with_status(warp::reply(), StatusCode::NO_CONTENT);
+    let reply = warp::reply::with_header(reply, "Access-Control-Allow-Methods", "GET");
+    let reply = warp::reply::with_header(reply, "Access-Control-Allow-Headers", "content-type");
+    Ok(with_cors_header(reply, origin))
+}
+
+/// Turns a rejected [`OriginNotAllowed`] into a 403; other rejections (404,
+/// method-not-allowed, etc.) are passed through for warp's default handling.
+async fn handle_rejection(rejection: warp::Rejection) -> Result<impl warp::Reply, warp::Rejection> {
+    if rejection.find::<OriginNotAllowed>().is_some() {
+        Ok(warp::reply::with_status("origin not allowed".to_owned(), StatusCode::FORBIDDEN))
+    } else {
+        Err(rejection)
     }
 }
 
@@ -351,6 +1095,10 @@ async fn main() {
     let opts_filter_clone = opts.clone();
     let opts_filter = warp::any().map(move || opts_filter_clone.clone());
 
+    let sessions: Sessions = Arc::new(Mutex::new(HashMap::new()));
+    let sessions_filter_clone = sessions.clone();
+    let sessions_filter = warp::any().map(move || sessions_filter_clone.clone());
+
     media_server::library_init(LoggingLevel::Debug).unwrap();
 
     if opts.port_range.is_some() {
@@ -360,34 +1108,66 @@ async fn main() {
     let websocket = warp::get()
         .and(warp::path::path("ws"))
         .and(warp::path::end())
+        .and(check_origin(opts.clone()))
         .and(warp::ws())
+        .and(opts_filter.clone())
+        .and(sessions_filter.clone())
+        .map(|origin: Option<String>, ws: warp::ws::Ws, opts: Arc<Opts>, sessions: Sessions| {
+            let reply = ws.on_upgrade(|w| on_websocket_upgrade(opts, sessions, w));
+            with_cors_header(reply, origin)
+        });
+
+    let whip_publish_route = warp::post()
+        .and(warp::path::path("whip"))
+        .and(warp::path::end())
         .and(opts_filter)
-        .map(|ws: warp::ws::Ws, opts: Arc<Opts>| ws.on_upgrade(|w| on_websocket_upgrade(opts, w)));
+        .and(sessions_filter.clone())
+        .and(warp::query::<WhipPublishQuery>())
+        .and(warp::body::bytes())
+        .and_then(whip_publish);
+
+    let whip_teardown_route = warp::delete()
+        .and(warp::path::path("whip"))
+        .and(warp::path::param())
+        .and(warp::path::end())
+        .and(sessions_filter)
+        .and_then(whip_teardown);
 
     let index = warp::get()
         .and(warp::path::end())
-        .map(|| warp::reply::html(include_str!("../resources/index.html")));
+        .and(check_origin(opts.clone()))
+        .map(|origin: Option<String>| with_cors_header(warp::reply::html(include_str!("../resources/index.html")), origin));
 
     let adapter = warp::get()
         .and(warp::path::path("adapter.js"))
         .and(warp::path::end())
-        .map(|| {
+        .and(check_origin(opts.clone()))
+        .map(|origin: Option<String>| {
             let adapter = include_str!("../resources/adapter.js");
-            warp::reply::with_header(adapter, "Content-Type", "application/javascript")
+            let reply = warp::reply::with_header(adapter, "Content-Type", "application/javascript");
+            with_cors_header(reply, origin)
         });
 
     let favicon = warp::get()
         .and(warp::path::path("favicon.ico"))
         .and(warp::path::end())
-        .map(|| {
+        .and(check_origin(opts.clone()))
+        .map(|origin: Option<String>| {
             let favicon = include_bytes!("../resources/favicon.ico");
-            warp::reply::with_header(favicon.as_ref(), "Content-Type", "image/vnd.microsoft.icon")
+            let reply = warp::reply::with_header(favicon.as_ref(), "Content-Type", "image/vnd.microsoft.icon");
+            with_cors_header(reply, origin)
         });
 
+    let preflight = warp::options().and(check_origin(opts.clone())).and_then(handle_preflight);
+
     let routes = websocket
+        .or(whip_publish_route)
+        .or(whip_teardown_route)
         .or(index)
         .or(adapter)
         .or(favicon)
+        .or(preflight)
+        .recover(handle_rejection)
         .with(warp::log("media_server_demo::http"));
 
     warp::serve(routes).run(opts.listen).await;
@@ -30,6 +30,69 @@ mod ffi {
         Text,
     }
 
+    #[repr(i32)]
+    enum DtlsRole {
+        Auto,
+        Client,
+        Server,
+    }
+
+    #[repr(i32)]
+    enum IceTransportType {
+        Udp,
+        Tcp,
+    }
+
+    #[repr(i32)]
+    enum IceCandidateType {
+        Host,
+        ServerReflexive,
+        PeerReflexive,
+        Relayed,
+    }
+
+    #[repr(i32)]
+    enum LogLevel {
+        Error,
+        Warning,
+        Info,
+        Debug,
+        UltraDebug,
+    }
+
+    /// One packet's worth of the transport-wide-cc arrival report: when we
+    /// (the receiver) saw it, compared against when the sender says it sent
+    /// it, per the `transport-wide-cc-extensions-01` header extension.
+    #[derive(Debug, Clone, Copy)]
+    struct TransportWideCcPacketFeedback {
+        sequence_number: u16,
+        send_time_ms: i64,
+        arrival_time_ms: i64,
+        payload_size: u32,
+    }
+
+    /// Per-[`RtpIncomingSourceGroupFacade`] counters, modelled loosely on
+    /// WebRTC's `RTCInboundRtpStreamStats`.
+    #[derive(Debug, Clone)]
+    struct IncomingSourceGroupStats {
+        packets_received: u64,
+        bytes_received: u64,
+        packets_lost: u64,
+        jitter_ms: f64,
+        bitrate_bps: u32,
+        last_ssrc: u32,
+        last_rid: String,
+    }
+
+    /// Connection-wide counters and transport state, modelled loosely on
+    /// WebRTC's `RTCIceCandidatePairStats`/`RTCTransportStats`.
+    #[derive(Debug, Clone, Copy)]
+    struct ConnectionStats {
+        dtls_state: DtlsIceTransportDtlsState,
+        ice_connected: bool,
+        round_trip_time_ms: f64,
+    }
+
     extern "Rust" {
         type DtlsIceTransportListenerRustAdapter;
         fn on_ice_timeout(self: &mut DtlsIceTransportListenerRustAdapter);
@@ -40,6 +103,13 @@ mod ffi {
             port: u16,
             priority: u32,
         );
+
+        fn dispatch_log_record(level: LogLevel, message: &str);
+
+        fn on_transport_wide_cc_feedback(
+            self: &mut DtlsIceTransportListenerRustAdapter,
+            feedback: Vec<TransportWideCcPacketFeedback>,
+        );
     }
 
     unsafe extern "C++" {
@@ -48,12 +118,15 @@ mod ffi {
         type DtlsConnectionHash;
         type DtlsIceTransportDtlsState;
         type MediaFrameType;
+        type DtlsRole;
+        type IceTransportType;
+        type IceCandidateType;
+        type LogLevel;
 
-        fn logger_enable_log(flag: bool);
-        fn logger_enable_debug(flag: bool);
-        fn logger_enable_ultra_debug(flag: bool);
+        fn logger_set_level(level: LogLevel);
 
         fn openssl_class_init() -> Result<()>;
+        fn openssl_class_init_with_certificate(certificate_pem: &str, private_key_pem: &str) -> Result<()>;
 
         fn dtls_connection_initialize() -> Result<()>;
         fn dtls_connection_get_certificate_fingerprint(hash: DtlsConnectionHash) -> Result<String>;
@@ -67,12 +140,14 @@ mod ffi {
         fn set_string(self: Pin<&mut PropertiesFacade>, key: &str, value: &str);
 
         type RtpIncomingSourceGroupFacade;
+        fn get_stats(self: &RtpIncomingSourceGroupFacade) -> IncomingSourceGroupStats;
 
         type RtpOutgoingSourceGroupFacade;
         fn add_transponder(self: Pin<&mut RtpOutgoingSourceGroupFacade>) -> UniquePtr<RtpStreamTransponderFacade>;
 
         type RtpStreamTransponderFacade;
         fn set_incoming(self: Pin<&mut RtpStreamTransponderFacade>, incoming: Pin<&mut RtpIncomingSourceGroupFacade>);
+        fn set_target_bitrate(self: Pin<&mut RtpStreamTransponderFacade>, bitrate_bps: u32);
 
         type RtpBundleTransportConnectionFacade;
         fn set_listener(
@@ -81,6 +156,19 @@ mod ffi {
         );
         fn set_remote_properties(self: Pin<&mut RtpBundleTransportConnectionFacade>, properties: &PropertiesFacade);
         fn set_local_properties(self: Pin<&mut RtpBundleTransportConnectionFacade>, properties: &PropertiesFacade);
+        /// The DTLS-SRTP profile the handshake settled on, as the
+        /// `SSL_get_selected_srtp_profile` name, or an empty string before
+        /// the handshake completes.
+        fn get_negotiated_srtp_protection_profile(self: &RtpBundleTransportConnectionFacade) -> String;
+        /// Connection-wide counters and transport state; see
+        /// [`RtpIncomingSourceGroupFacade::get_stats`] for per-track counters.
+        fn get_stats(self: &RtpBundleTransportConnectionFacade) -> ConnectionStats;
+        fn set_remote_fingerprint(
+            self: Pin<&mut RtpBundleTransportConnectionFacade>,
+            hash: DtlsConnectionHash,
+            value: &str,
+        );
+        fn set_dtls_role(self: Pin<&mut RtpBundleTransportConnectionFacade>, role: DtlsRole);
         fn add_incoming_source_group(
             self: Pin<&mut RtpBundleTransportConnectionFacade>,
             kind: MediaFrameType,
@@ -96,7 +184,16 @@ mod ffi {
             media_ssrc: u32,
             rtx_ssrc: u32,
         ) -> Result<UniquePtr<RtpOutgoingSourceGroupFacade>>;
-        fn add_remote_candidate(self: Pin<&mut RtpBundleTransportConnectionFacade>, ip: &str, port: u16);
+        fn add_remote_candidate(
+            self: Pin<&mut RtpBundleTransportConnectionFacade>,
+            ip: &str,
+            port: u16,
+            transport: IceTransportType,
+            kind: IceCandidateType,
+            priority: u32,
+            related_ip: &str,
+            related_port: u16,
+        );
 
         type RtpBundleTransportFacade;
         fn new_rtp_bundle_transport(port: u16) -> Result<UniquePtr<RtpBundleTransportFacade>>;
@@ -132,11 +229,40 @@ impl std::fmt::Debug for DtlsIceTransportDtlsState {
     }
 }
 
+impl std::fmt::Debug for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            LogLevel::Error => f.write_str("Error"),
+            LogLevel::Warning => f.write_str("Warning"),
+            LogLevel::Info => f.write_str("Info"),
+            LogLevel::Debug => f.write_str("Debug"),
+            LogLevel::UltraDebug => f.write_str("UltraDebug"),
+            _ => f.write_str("Unknown"),
+        }
+    }
+}
+
+/// Called by the C++ side for every log record; forwards it into the `log`
+/// facade under a consistent target so host applications can filter native
+/// log volume with `RUST_LOG`/env-filter instead of recompiling.
+fn dispatch_log_record(level: LogLevel, message: &str) {
+    let level = match level {
+        LogLevel::Error => log::Level::Error,
+        LogLevel::Warning => log::Level::Warn,
+        LogLevel::Info => log::Level::Info,
+        LogLevel::Debug => log::Level::Debug,
+        _ => log::Level::Trace,
+    };
+
+    log::log!(target: "media_server::native", level, "{}", message);
+}
+
 #[allow(unused_variables)]
 pub trait DtlsIceTransportListener: Send {
     fn on_ice_timeout(&mut self) {}
     fn on_dtls_state_changed(&mut self, state: DtlsIceTransportDtlsState) {}
     fn on_remote_ice_candidate_activated(&mut self, ip: &str, port: u16, priority: u32) {}
+    fn on_transport_wide_cc_feedback(&mut self, feedback: Vec<TransportWideCcPacketFeedback>) {}
 }
 
 pub struct DtlsIceTransportListenerRustAdapter(Box<dyn DtlsIceTransportListener>);
@@ -153,6 +279,10 @@ impl DtlsIceTransportListenerRustAdapter {
     fn on_remote_ice_candidate_activated(&mut self, ip: &str, port: u16, priority: u32) {
         self.0.on_remote_ice_candidate_activated(ip, port, priority)
     }
+
+    fn on_transport_wide_cc_feedback(&mut self, feedback: Vec<TransportWideCcPacketFeedback>) {
+        self.0.on_transport_wide_cc_feedback(feedback)
+    }
 }
 
 impl<T> From<T> for DtlsIceTransportListenerRustAdapter
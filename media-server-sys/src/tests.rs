@@ -16,9 +16,7 @@ fn library_init() -> Result<(), Box<dyn std::error::Error>> {
 
     *is_init = true;
 
-    logger_enable_log(true);
-    logger_enable_debug(true);
-    logger_enable_ultra_debug(false);
+    logger_set_level(LogLevel::Debug);
 
     openssl_class_init()?;
 
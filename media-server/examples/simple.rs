@@ -5,15 +5,15 @@ use futures::future::Either;
 use futures_timer::Delay;
 
 use media_server::{
-    DtlsConnectionHash, DtlsIceTransportDtlsState, DtlsIceTransportListener, Properties, Result, RtpBundleTransport,
-    RtpBundleTransportConnection,
+    srtp_protection_profiles_property, DtlsConnectionHash, DtlsIceTransportListener, DtlsState, IceCandidateType,
+    IceTransportType, Properties, Result, RtpBundleTransport, RtpBundleTransportConnection, SRTP_PROTECTION_PROFILES,
 };
 
 struct WaitForConnectionListener(Option<oneshot::Sender<()>>);
 
 impl DtlsIceTransportListener for WaitForConnectionListener {
-    fn on_dtls_state_changed(&mut self, state: DtlsIceTransportDtlsState) {
-        if state == DtlsIceTransportDtlsState::Connected {
+    fn on_dtls_state_changed(&mut self, state: DtlsState) {
+        if state == DtlsState::Connected {
             // Ignore failure, panicking in here is an abort.
             if let Some(sender) = self.0.take() {
                 let _ = sender.send(());
@@ -46,7 +46,7 @@ fn create_test_transport(
     properties.set_string("dtls.hash", "SHA-256");
     properties.set_string("dtls.fingerprint", &fingerprint);
     properties.set_bool("disableSTUNKeepAlive", true);
-    properties.set_string("srtpProtectionProfiles", "");
+    properties.set_string("srtpProtectionProfiles", &srtp_protection_profiles_property(SRTP_PROTECTION_PROFILES));
 
     let username = local_username.to_owned() + ":" + remote_username;
     let connection = transport.add_ice_transport(username.as_str(), &properties)?;
@@ -67,8 +67,15 @@ fn main() -> Result<()> {
     let one = create_test_transport("one", "two", "active")?;
     let two = create_test_transport("two", "one", "passive")?;
 
-    two.connection
-        .add_remote_candidate("127.0.0.1", one.transport.get_local_port());
+    two.connection.add_remote_candidate(
+        "127.0.0.1",
+        one.transport.get_local_port(),
+        &IceTransportType::Udp,
+        &IceCandidateType::Host,
+        0,
+        None,
+        None,
+    );
 
     futures::executor::block_on(async {
         let connected = futures::future::try_join(one.receiver, two.receiver);
@@ -0,0 +1,332 @@
+//! Delay-based bandwidth estimation driven by transport-wide-cc feedback, in
+//! the shape of the Google Congestion Control algorithm: packets are bucketed
+//! into send-time "groups", a trendline fit over successive groups' delay
+//! variation classifies the link as overusing/underusing/normal, and an AIMD
+//! controller turns that classification into a target send bitrate. Fed by
+//! [`crate::DtlsIceTransportListener::on_transport_wide_cc_feedback`] and
+//! consumed by [`crate::RtpStreamTransponder::set_target_bitrate`].
+
+use crate::TransportWideCcPacketFeedback;
+
+/// Packets whose reported send times fall within this window are treated as
+/// one burst, so per-packet jitter in a single scheduler tick doesn't get
+/// mistaken for delay variation between bursts.
+const BURST_TIME_WINDOW_MS: i64 = 5;
+
+/// How many (accumulated delay, arrival time) samples the trendline fit
+/// looks back over.
+const TRENDLINE_WINDOW_SIZE: usize = 20;
+
+/// Scales the fitted slope before comparing it against the adaptive
+/// threshold; matches the gain used by the reference implementation this is
+/// modeled on.
+const TRENDLINE_GAIN: f64 = 4.0;
+
+/// An overuse signal must persist longer than this before the detector
+/// reports overuse; underuse and normal take effect immediately.
+const OVERUSE_TIME_THRESHOLD_MS: f64 = 10.0;
+
+/// Multiplicative decrease applied to the target bitrate on overuse.
+const DECREASE_FACTOR: f64 = 0.85;
+
+/// Multiplicative increase applied to the target bitrate in slow-start.
+const SLOW_START_INCREASE_FACTOR: f64 = 1.08;
+
+/// Additive increase applied to the target bitrate per estimated RTT once
+/// out of slow-start, in bytes.
+const ADDITIVE_INCREASE_BYTES_PER_RTT: f64 = 1000.0;
+
+/// A fallback RTT used for the additive-increase step until we have a better
+/// one; this module doesn't currently compute a real RTT estimate.
+const ASSUMED_RTT_MS: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UsageState {
+    Normal,
+    Overusing,
+    Underusing,
+}
+
+/// One burst of packets that arrived close enough together in send time to
+/// be treated as a single group for delay-variation purposes.
+struct PacketGroup {
+    first_send_time_ms: i64,
+    last_send_time_ms: i64,
+    complete_time_ms: i64,
+}
+
+/// Fits a line through the last [`TRENDLINE_WINDOW_SIZE`] (arrival time,
+/// accumulated delay) samples and reports whether its slope indicates a
+/// growing, shrinking, or steady queue.
+struct TrendlineEstimator {
+    samples: std::collections::VecDeque<(f64, f64)>,
+    accumulated_delay_ms: f64,
+    threshold: f64,
+    state: UsageState,
+    time_over_threshold_ms: f64,
+}
+
+impl TrendlineEstimator {
+    fn new() -> Self {
+        TrendlineEstimator {
+            samples: std::collections::VecDeque::with_capacity(TRENDLINE_WINDOW_SIZE),
+            accumulated_delay_ms: 0.0,
+            // Starting threshold from the reference implementation; it
+            // drifts towards whatever the link actually looks like.
+            threshold: 12.5,
+            state: UsageState::Normal,
+            time_over_threshold_ms: 0.0,
+        }
+    }
+
+    /// Feeds one inter-group delay-variation sample (`d_recv - d_send`,
+    /// milliseconds) observed `duration_ms` after the previous one, and
+    /// returns the resulting usage classification.
+    fn update(&mut self, delay_variation_ms: f64, arrival_time_ms: f64, duration_ms: f64) -> UsageState {
+        self.accumulated_delay_ms += delay_variation_ms;
+
+        self.samples.push_back((arrival_time_ms, self.accumulated_delay_ms));
+        if self.samples.len() > TRENDLINE_WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+
+        if self.samples.len() < 2 {
+            return self.state;
+        }
+
+        let slope = self.fit_slope();
+        let modified_slope = TRENDLINE_GAIN * slope * (self.samples.len() as f64);
+
+        self.classify(modified_slope, duration_ms);
+        self.adapt_threshold(modified_slope);
+
+        self.state
+    }
+
+    /// Ordinary least-squares slope of accumulated delay against arrival
+    /// time over the current window.
+    fn fit_slope(&self) -> f64 {
+        let n = self.samples.len() as f64;
+        let mean_x = self.samples.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = self.samples.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let mut numerator = 0.0;
+        let mut denominator = 0.0;
+        for &(x, y) in &self.samples {
+            numerator += (x - mean_x) * (y - mean_y);
+            denominator += (x - mean_x) * (x - mean_x);
+        }
+
+        if denominator.abs() < f64::EPSILON {
+            0.0
+        } else {
+            numerator / denominator
+        }
+    }
+
+    fn classify(&mut self, modified_slope: f64, duration_ms: f64) {
+        if modified_slope > self.threshold {
+            self.time_over_threshold_ms += duration_ms;
+
+            if self.time_over_threshold_ms > OVERUSE_TIME_THRESHOLD_MS {
+                self.state = UsageState::Overusing;
+            }
+        } else if modified_slope < -self.threshold {
+            self.time_over_threshold_ms = 0.0;
+            self.state = UsageState::Underusing;
+        } else {
+            self.time_over_threshold_ms = 0.0;
+            self.state = UsageState::Normal;
+        }
+    }
+
+    /// Lets the threshold itself drift towards the magnitude of what we're
+    /// actually measuring, so a persistently noisy or persistently quiet
+    /// link doesn't get stuck misclassifying everything as over/underuse.
+    fn adapt_threshold(&mut self, modified_slope: f64) {
+        let ki = if modified_slope.abs() < self.threshold { 0.039 } else { 0.0087 };
+        let step = ki * (modified_slope.abs() - self.threshold);
+        self.threshold = (self.threshold + step).clamp(6.0, 600.0);
+    }
+}
+
+/// AIMD controller over a single target bitrate: backs off multiplicatively
+/// on overuse, grows additively (or multiplicatively during slow-start)
+/// while the link looks normal, and holds steady on underuse.
+struct AimdRateController {
+    target_bitrate_bps: f64,
+    in_slow_start: bool,
+}
+
+impl AimdRateController {
+    fn new(initial_bitrate_bps: u32) -> Self {
+        AimdRateController {
+            target_bitrate_bps: initial_bitrate_bps as f64,
+            in_slow_start: true,
+        }
+    }
+
+    fn update(&mut self, state: UsageState) {
+        match state {
+            UsageState::Overusing => {
+                self.target_bitrate_bps *= DECREASE_FACTOR;
+                self.in_slow_start = false;
+            }
+            UsageState::Normal => {
+                if self.in_slow_start {
+                    self.target_bitrate_bps *= SLOW_START_INCREASE_FACTOR;
+                } else {
+                    let bits_per_rtt = ADDITIVE_INCREASE_BYTES_PER_RTT * 8.0;
+                    self.target_bitrate_bps += bits_per_rtt * (1000.0 / ASSUMED_RTT_MS);
+                }
+            }
+            UsageState::Underusing => {}
+        }
+    }
+}
+
+/// Estimates a forwarding bitrate from transport-wide-cc feedback, per
+/// [Google Congestion Control](https://datatracker.ietf.org/doc/html/draft-ietf-rmcat-gcc).
+/// Group packets by send-time burst, turn consecutive groups' delay
+/// variation into an overuse/underuse/normal classification via a trendline
+/// fit, and drive an AIMD controller off that classification. The result is
+/// clamped against a receiver-estimated maximum (e.g. from REMB) and is meant
+/// to be applied per outgoing group via [`crate::RtpStreamTransponder::set_target_bitrate`].
+pub struct BandwidthEstimator {
+    trendline: TrendlineEstimator,
+    controller: AimdRateController,
+    current_group: Option<PacketGroup>,
+    previous_group: Option<PacketGroup>,
+    receiver_estimated_max_bps: Option<u32>,
+}
+
+impl BandwidthEstimator {
+    pub fn new(initial_bitrate_bps: u32) -> Self {
+        BandwidthEstimator {
+            trendline: TrendlineEstimator::new(),
+            controller: AimdRateController::new(initial_bitrate_bps),
+            current_group: None,
+            previous_group: None,
+            receiver_estimated_max_bps: None,
+        }
+    }
+
+    /// Sets the receiver-estimated maximum (e.g. from a REMB report), which
+    /// clamps every subsequent [`Self::target_bitrate_bps`].
+    pub fn set_receiver_estimated_max_bitrate(&mut self, bitrate_bps: u32) {
+        self.receiver_estimated_max_bps = Some(bitrate_bps);
+    }
+
+    /// Feeds one transport-wide-cc feedback report (already ordered by
+    /// sequence number) into the estimator and returns the updated target
+    /// bitrate in bits per second.
+    pub fn on_packet_feedback(&mut self, feedback: &[TransportWideCcPacketFeedback]) -> u32 {
+        for packet in feedback {
+            self.on_packet(packet);
+        }
+
+        self.target_bitrate_bps()
+    }
+
+    fn on_packet(&mut self, packet: &TransportWideCcPacketFeedback) {
+        match &mut self.current_group {
+            Some(group) if packet.send_time_ms - group.first_send_time_ms <= BURST_TIME_WINDOW_MS => {
+                group.last_send_time_ms = group.last_send_time_ms.max(packet.send_time_ms);
+                group.complete_time_ms = group.complete_time_ms.max(packet.arrival_time_ms);
+            }
+            _ => {
+                let finished = self.current_group.take();
+                self.current_group = Some(PacketGroup {
+                    first_send_time_ms: packet.send_time_ms,
+                    last_send_time_ms: packet.send_time_ms,
+                    complete_time_ms: packet.arrival_time_ms,
+                });
+
+                if let Some(finished) = finished {
+                    self.on_group_complete(finished);
+                }
+            }
+        }
+    }
+
+    fn on_group_complete(&mut self, group: PacketGroup) {
+        if let Some(previous) = self.previous_group.replace(PacketGroup {
+            first_send_time_ms: group.first_send_time_ms,
+            last_send_time_ms: group.last_send_time_ms,
+            complete_time_ms: group.complete_time_ms,
+        }) {
+            let d_send = (group.first_send_time_ms - previous.first_send_time_ms) as f64;
+            let d_recv = (group.complete_time_ms - previous.complete_time_ms) as f64;
+            let delay_variation_ms = d_recv - d_send;
+
+            let state = self.trendline.update(delay_variation_ms, group.complete_time_ms as f64, d_recv.max(1.0));
+            self.controller.update(state);
+        }
+    }
+
+    /// The current target send bitrate, clamped against the
+    /// receiver-estimated maximum if one has been set.
+    pub fn target_bitrate_bps(&self) -> u32 {
+        let target = self.controller.target_bitrate_bps.max(0.0) as u32;
+
+        match self.receiver_estimated_max_bps {
+            Some(max) => target.min(max),
+            None => target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(sequence_number: u16, send_time_ms: i64, arrival_time_ms: i64) -> TransportWideCcPacketFeedback {
+        TransportWideCcPacketFeedback {
+            sequence_number,
+            send_time_ms,
+            arrival_time_ms,
+            payload_size: 1200,
+        }
+    }
+
+    #[test]
+    fn slow_start_increases_with_no_delay_growth() {
+        let mut estimator = BandwidthEstimator::new(300_000);
+        let initial = estimator.target_bitrate_bps();
+
+        let mut feedback = Vec::new();
+        for i in 0..60 {
+            let t = i as i64 * 20;
+            feedback.push(packet(i, t, t));
+        }
+
+        let target = estimator.on_packet_feedback(&feedback);
+        assert!(target > initial, "expected slow-start growth, got {} <= {}", target, initial);
+    }
+
+    #[test]
+    fn sustained_growing_delay_triggers_decrease() {
+        let mut estimator = BandwidthEstimator::new(300_000);
+        let initial = estimator.target_bitrate_bps();
+
+        let mut feedback = Vec::new();
+        for i in 0..60 {
+            let send_t = i as i64 * 20;
+            // Each group arrives later and later relative to its send time,
+            // i.e. the receive-side queue is growing.
+            let arrival_t = send_t + i as i64 * 5;
+            feedback.push(packet(i, send_t, arrival_t));
+        }
+
+        let target = estimator.on_packet_feedback(&feedback);
+        assert!(target < initial, "expected overuse decrease, got {} >= {}", target, initial);
+    }
+
+    #[test]
+    fn clamps_to_receiver_estimated_max() {
+        let mut estimator = BandwidthEstimator::new(300_000);
+        estimator.set_receiver_estimated_max_bitrate(250_000);
+
+        assert_eq!(estimator.target_bitrate_bps(), 250_000);
+    }
+}
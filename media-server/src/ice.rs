@@ -0,0 +1,156 @@
+//! Local ICE candidate gathering (RFC 5245 §4.1) for [`crate::RtpBundleTransport`].
+//!
+//! `RtpBundleTransportConnection::add_remote_candidate` only ever accepts
+//! candidates handed to it from the outside; there is no equivalent on the
+//! native side for finding out what *we* should advertise. This enumerates
+//! local interface addresses for `get_local_port`, and optionally gathers a
+//! server-reflexive and/or relay candidate from a configured STUN/TURN
+//! server, so callers have everything they need for trickle-ICE signalling.
+
+use std::net::{SocketAddr, UdpSocket};
+use std::time::Duration;
+
+use crate::turn::{self, TurnAllocation, TurnServerConfig};
+use crate::Result;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum IceCandidateType {
+    Host,
+    ServerReflexive,
+    Relay,
+}
+
+impl IceCandidateType {
+    fn type_preference(self) -> u32 {
+        match self {
+            IceCandidateType::Host => 126,
+            IceCandidateType::ServerReflexive => 100,
+            IceCandidateType::Relay => 0,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IceCandidateType::Host => "host",
+            IceCandidateType::ServerReflexive => "srflx",
+            IceCandidateType::Relay => "relay",
+        }
+    }
+}
+
+/// RFC 5245 §4.1.2.1 candidate priority: `2^24 * type_pref + 2^8 * local_pref + (256 - component_id)`.
+pub fn candidate_priority(kind: IceCandidateType, local_preference: u16, component_id: u16) -> u32 {
+    2u32.pow(24) * kind.type_preference() + 2u32.pow(8) * local_preference as u32 + (256 - component_id as u32)
+}
+
+fn local_addresses() -> Result<Vec<std::net::IpAddr>> {
+    let addresses = if_addrs::get_if_addrs()?
+        .into_iter()
+        .map(|iface| iface.ip())
+        .filter(|ip| !ip.is_loopback())
+        .collect();
+
+    Ok(addresses)
+}
+
+/// Sends a STUN Binding request to `stun_server` from an ephemeral local port
+/// and returns the reflexive address the server observed.
+fn gather_server_reflexive_address(stun_server: SocketAddr) -> Result<SocketAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.set_read_timeout(Some(Duration::from_secs(2)))?;
+
+    let (request, transaction_id) = crate::stun::binding_request();
+    socket.send_to(&request, stun_server)?;
+
+    let mut response = [0u8; 512];
+    let (len, from) = socket.recv_from(&mut response)?;
+    if from != stun_server {
+        return Err("received a STUN response from an unexpected address".into());
+    }
+
+    crate::stun::parse_xor_mapped_address(&response[..len], &transaction_id)
+}
+
+/// One gathered local candidate, ready to be signaled to the remote peer
+/// (e.g. rendered as an `a=candidate` line or sent over a trickle-ICE channel).
+#[derive(Debug, Clone)]
+pub struct IceCandidate {
+    pub foundation: String,
+    pub component: u16,
+    pub transport: String,
+    pub priority: u32,
+    pub ip: String,
+    pub port: u16,
+    pub typ: IceCandidateType,
+}
+
+/// Result of a [`gather_local_candidates`] pass: the candidates themselves,
+/// plus the live [`TurnAllocation`] (if a TURN server was configured) so the
+/// caller can install permissions for the remote peer and refresh it before
+/// `TurnAllocation::lifetime` runs out.
+pub struct GatheredCandidates {
+    pub candidates: Vec<IceCandidate>,
+    pub turn_allocation: Option<TurnAllocation>,
+}
+
+/// Enumerates host candidates for `local_port` on every non-loopback local
+/// interface, optionally a server-reflexive candidate obtained from
+/// `stun_server`, and optionally a relay candidate obtained by allocating on
+/// `turn_server`.
+pub fn gather_local_candidates(
+    local_port: u16,
+    component: u16,
+    stun_server: Option<SocketAddr>,
+    turn_server: Option<&TurnServerConfig>,
+) -> Result<GatheredCandidates> {
+    let mut candidates = Vec::new();
+
+    for (index, address) in local_addresses()?.into_iter().enumerate() {
+        let local_preference = 65535 - index as u16;
+
+        candidates.push(IceCandidate {
+            foundation: "1".to_owned(),
+            component,
+            transport: "udp".to_owned(),
+            priority: candidate_priority(IceCandidateType::Host, local_preference, component),
+            ip: address.to_string(),
+            port: local_port,
+            typ: IceCandidateType::Host,
+        });
+    }
+
+    if let Some(stun_server) = stun_server {
+        let reflexive = gather_server_reflexive_address(stun_server)?;
+
+        candidates.push(IceCandidate {
+            foundation: "2".to_owned(),
+            component,
+            transport: "udp".to_owned(),
+            priority: candidate_priority(IceCandidateType::ServerReflexive, 65535, component),
+            ip: reflexive.ip().to_string(),
+            port: reflexive.port(),
+            typ: IceCandidateType::ServerReflexive,
+        });
+    }
+
+    let turn_allocation = match turn_server {
+        Some(turn_server) => {
+            let allocation = turn::allocate(turn_server)?;
+
+            candidates.push(IceCandidate {
+                foundation: "3".to_owned(),
+                component,
+                transport: "udp".to_owned(),
+                priority: candidate_priority(IceCandidateType::Relay, 65535, component),
+                ip: allocation.relayed_address.ip().to_string(),
+                port: allocation.relayed_address.port(),
+                typ: IceCandidateType::Relay,
+            });
+
+            Some(allocation)
+        }
+        None => None,
+    };
+
+    Ok(GatheredCandidates { candidates, turn_allocation })
+}
@@ -1,5 +1,13 @@
+use std::str::FromStr;
+
 use media_server_sys as bridge;
 
+pub mod congestion;
+pub mod ice;
+pub mod sdp;
+mod stun;
+pub mod turn;
+
 mod cxx {
     pub use media_server_sys::UniquePtr;
 }
@@ -18,6 +26,21 @@ pub enum LoggingLevel {
     UltraDebug,
 }
 
+impl Into<bridge::LogLevel> for LoggingLevel {
+    fn into(self) -> bridge::LogLevel {
+        match self {
+            // The native logger has no true "off" threshold, so `None` just
+            // asks for the quietest one: native log records still reach
+            // `dispatch_log_record`, but every caller-visible line now goes
+            // through `log`/`tracing`, so `RUST_LOG` can filter the rest.
+            LoggingLevel::None => bridge::LogLevel::Error,
+            LoggingLevel::Default => bridge::LogLevel::Info,
+            LoggingLevel::Debug => bridge::LogLevel::Debug,
+            LoggingLevel::UltraDebug => bridge::LogLevel::UltraDebug,
+        }
+    }
+}
+
 pub fn library_init(logging: LoggingLevel) -> Result<()> {
     let mut is_init = INIT_MUTEX.lock();
 
@@ -27,12 +50,34 @@ pub fn library_init(logging: LoggingLevel) -> Result<()> {
 
     *is_init = true;
 
-    // TODO: Expose the logging config to consumers.
-    bridge::logger_enable_log(logging >= LoggingLevel::Default);
-    bridge::logger_enable_debug(logging >= LoggingLevel::Debug);
-    bridge::logger_enable_ultra_debug(logging >= LoggingLevel::UltraDebug);
+    library_init_impl(logging, None)
+}
+
+/// Like [`library_init`], but provisions DTLS with a caller-supplied
+/// PEM-encoded certificate and private key instead of the self-signed one
+/// `dtls_connection_initialize` generates on the fly. Use this to pin a
+/// stable, externally-known fingerprint across restarts, or to share one
+/// identity across multiple instances — [`get_certificate_fingerprint`]
+/// will then report the hash of `certificate_pem` rather than a fresh one.
+pub fn library_init_with_certificate(logging: LoggingLevel, certificate_pem: &str, private_key_pem: &str) -> Result<()> {
+    let mut is_init = INIT_MUTEX.lock();
+
+    if *is_init {
+        return Ok(());
+    }
+
+    *is_init = true;
+
+    library_init_impl(logging, Some((certificate_pem, private_key_pem)))
+}
+
+fn library_init_impl(logging: LoggingLevel, certificate: Option<(&str, &str)>) -> Result<()> {
+    bridge::logger_set_level(logging.into());
 
-    bridge::openssl_class_init()?;
+    match certificate {
+        Some((certificate_pem, private_key_pem)) => bridge::openssl_class_init_with_certificate(certificate_pem, private_key_pem)?,
+        None => bridge::openssl_class_init()?,
+    }
 
     // It is unfortunate that this is global state.
     bridge::dtls_connection_initialize()?;
@@ -40,6 +85,7 @@ pub fn library_init(logging: LoggingLevel) -> Result<()> {
     Ok(())
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum DtlsConnectionHash {
     Sha1,
     Sha224,
@@ -62,11 +108,130 @@ impl Into<bridge::DtlsConnectionHash> for DtlsConnectionHash {
     }
 }
 
+/// Translates a parsed `a=fingerprint` hash function into the hash the native
+/// library's certificate/fingerprint APIs expect. `Md5`/`Md2` and anything
+/// unrecognized fall back to `UnknownHash`, same as the repo's other
+/// SDP-enum-to-bridge-enum conversions (see the `From<&IceTransportType>`
+/// impl) — those hashes are long deprecated for DTLS-SRTP and the native side
+/// has no dedicated variant for them.
+impl From<&crate::sdp::enums::FingerprintHashFunction> for DtlsConnectionHash {
+    fn from(hash: &crate::sdp::enums::FingerprintHashFunction) -> Self {
+        use crate::sdp::enums::FingerprintHashFunction;
+
+        match hash {
+            FingerprintHashFunction::Sha1 => DtlsConnectionHash::Sha1,
+            FingerprintHashFunction::Sha224 => DtlsConnectionHash::Sha224,
+            FingerprintHashFunction::Sha256 => DtlsConnectionHash::Sha256,
+            FingerprintHashFunction::Sha384 => DtlsConnectionHash::Sha384,
+            FingerprintHashFunction::Sha512 => DtlsConnectionHash::Sha512,
+            FingerprintHashFunction::Md5 | FingerprintHashFunction::Md2 | FingerprintHashFunction::Unknown(_) => {
+                DtlsConnectionHash::UnknownHash
+            }
+        }
+    }
+}
+
+impl DtlsConnectionHash {
+    /// The name expected by the native library's `dtls.hash` `Properties`
+    /// value (OpenSSL's `EVP_get_digestbyname` spelling), as opposed to the
+    /// lowercase `a=fingerprint` SDP token (`sha-256` vs. `SHA-256`).
+    pub fn property_name(&self) -> &'static str {
+        match self {
+            DtlsConnectionHash::Sha1 => "SHA-1",
+            DtlsConnectionHash::Sha224 => "SHA-224",
+            DtlsConnectionHash::Sha256 => "SHA-256",
+            DtlsConnectionHash::Sha384 => "SHA-384",
+            DtlsConnectionHash::Sha512 => "SHA-512",
+            DtlsConnectionHash::UnknownHash => "SHA-256",
+        }
+    }
+}
+
+/// Which side of the DTLS handshake a connection should play.
+///
+/// Translates the `a=setup:` value *we* are announcing in our own SDP:
+/// `active` means we dial out as the client, `passive` means we wait as the
+/// server, and `actpass` (only valid when we haven't committed to a role
+/// yet) picks server as the safe default. `Auto` leaves the choice to the
+/// native library, matching the pre-existing unconfigured behavior.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Role {
+    Auto,
+    Client,
+    Server,
+}
+
+impl Role {
+    pub fn from_local_setup(setup: &str) -> Role {
+        match setup {
+            "active" => Role::Client,
+            "passive" | "actpass" => Role::Server,
+            _ => Role::Auto,
+        }
+    }
+}
+
+impl Into<bridge::DtlsRole> for Role {
+    fn into(self) -> bridge::DtlsRole {
+        match self {
+            Role::Auto => bridge::DtlsRole::Auto,
+            Role::Client => bridge::DtlsRole::Client,
+            Role::Server => bridge::DtlsRole::Server,
+        }
+    }
+}
+
 pub fn get_certificate_fingerprint(hash: DtlsConnectionHash) -> Result<String> {
     let fingerprint = bridge::dtls_connection_get_certificate_fingerprint(hash.into())?;
     Ok(fingerprint)
 }
 
+/// Returns the local SHA-256 certificate fingerprint as a `certhash`: a
+/// multihash (`0x12` sha2-256 code, `0x20` length, 32 digest bytes) rendered
+/// in multibase base64url, the exact form browsers and libp2p-style
+/// transports expect for `a=tls-id`/WebTransport certificate pinning.
+pub fn get_certificate_fingerprint_certhash() -> Result<String> {
+    let fingerprint = get_certificate_fingerprint(DtlsConnectionHash::Sha256)?;
+
+    let digest: std::result::Result<Vec<u8>, _> = fingerprint.split(':').map(|byte| u8::from_str_radix(byte, 16)).collect();
+    let digest = digest?;
+
+    let mut multihash = Vec::with_capacity(2 + digest.len());
+    multihash.push(0x12); // sha2-256
+    multihash.push(0x20); // 32 byte digest
+    multihash.extend_from_slice(&digest);
+
+    Ok(format!("u{}", base64url_nopad(&multihash)))
+}
+
+/// A minimal base64url (RFC 4648 §5) encoder without padding, matching the
+/// multibase `u` prefix convention: not worth pulling in a whole crate for
+/// the one certhash string we need to render.
+fn base64url_nopad(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    let mut result = String::with_capacity((bytes.len() + 2) / 3 * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied();
+        let b2 = chunk.get(2).copied();
+
+        result.push(ALPHABET[(b0 >> 2) as usize] as char);
+        result.push(ALPHABET[((b0 & 0x03) << 4 | b1.unwrap_or(0) >> 4) as usize] as char);
+
+        if let Some(b1) = b1 {
+            result.push(ALPHABET[((b1 & 0x0f) << 2 | b2.unwrap_or(0) >> 6) as usize] as char);
+        }
+
+        if let Some(b2) = b2 {
+            result.push(ALPHABET[(b2 & 0x3f) as usize] as char);
+        }
+    }
+
+    result
+}
+
 pub fn set_port_range(range: Option<(u16, u16)>) -> Result<()> {
     // TODO: It looks like resetting the range to unrestricted may be broken in the library.
     let (min, max) = range.unwrap_or((0, 0));
@@ -100,14 +265,148 @@ impl Default for Properties {
     }
 }
 
-pub use bridge::DtlsIceTransportListener;
+pub use crate::sdp::enums::SrtpProtectionProfile;
+
+/// The SRTP protection profiles we're able to negotiate, in preference order
+/// (strongest/most modern first).
+pub const SRTP_PROTECTION_PROFILES: &[SrtpProtectionProfile] = &[
+    SrtpProtectionProfile::AeadAes256Gcm,
+    SrtpProtectionProfile::AeadAes128Gcm,
+    SrtpProtectionProfile::Aes128CmSha1_80,
+];
+
+/// Renders `profiles` as the colon-separated list the `srtpProtectionProfiles`
+/// property expects, e.g. `SRTP_AEAD_AES_256_GCM:SRTP_AES128_CM_SHA1_80`.
+/// There's nothing in SDP to intersect this against — DTLS-SRTP profile
+/// selection happens inside the TLS `use_srtp` extension, not `a=crypto` —
+/// so, same as offering ciphersuites in a TLS ClientHello, we just advertise
+/// every profile we support and let the handshake itself pick one.
+pub fn srtp_protection_profiles_property(profiles: &[SrtpProtectionProfile]) -> String {
+    profiles.iter().map(AsRef::as_ref).collect::<Vec<&str>>().join(":")
+}
+
+/// The DTLS connection's lifecycle, translated from the bridge's
+/// `DtlsIceTransportDtlsState` so consumers aren't tied to the cxx-generated
+/// type: await `Connected` before sending media, react to `Failed` (also
+/// raised on a [`RtpBundleTransportConnection::set_remote_fingerprint`]
+/// mismatch) to trigger renegotiation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DtlsState {
+    New,
+    Connecting,
+    Connected,
+    Closed,
+    Failed,
+}
+
+impl From<bridge::DtlsIceTransportDtlsState> for DtlsState {
+    fn from(state: bridge::DtlsIceTransportDtlsState) -> Self {
+        match state {
+            bridge::DtlsIceTransportDtlsState::New => DtlsState::New,
+            bridge::DtlsIceTransportDtlsState::Connecting => DtlsState::Connecting,
+            bridge::DtlsIceTransportDtlsState::Connected => DtlsState::Connected,
+            bridge::DtlsIceTransportDtlsState::Closed => DtlsState::Closed,
+            _ => DtlsState::Failed,
+        }
+    }
+}
+
+/// Re-exported so callers building a [`RtpBundleTransportConnection::add_remote_candidate`]
+/// call don't need a direct `semantic_sdp` dependency.
+pub use crate::sdp::enums::{IceCandidateType, IceTransportType};
+
+impl From<&IceTransportType> for bridge::IceTransportType {
+    fn from(transport: &IceTransportType) -> Self {
+        match transport {
+            IceTransportType::Udp => bridge::IceTransportType::Udp,
+            // The native library only routes candidates over UDP or TCP; an
+            // unrecognized token is safest treated as TCP, since silently
+            // gathering it as UDP could let a server-reflexive/relayed
+            // candidate bind to the wrong underlying socket.
+            IceTransportType::Tcp | IceTransportType::Unknown(_) => bridge::IceTransportType::Tcp,
+        }
+    }
+}
+
+impl From<&IceCandidateType> for bridge::IceCandidateType {
+    fn from(kind: &IceCandidateType) -> Self {
+        match kind {
+            IceCandidateType::Host => bridge::IceCandidateType::Host,
+            IceCandidateType::ServerReflexive => bridge::IceCandidateType::ServerReflexive,
+            IceCandidateType::PeerReflexive => bridge::IceCandidateType::PeerReflexive,
+            // `relay` is the only candidate type with no ICE-priority-derived
+            // fallback meaning, so an unrecognized token is safest mapped
+            // here rather than assumed directly reachable.
+            IceCandidateType::Relayed | IceCandidateType::Unknown(_) => bridge::IceCandidateType::Relayed,
+        }
+    }
+}
+
+/// One packet's worth of transport-wide-cc arrival report; see
+/// [`crate::congestion`] for how these are turned into a bandwidth estimate.
+pub type TransportWideCcPacketFeedback = bridge::TransportWideCcPacketFeedback;
+
+/// Per-[`RtpIncomingSourceGroup`] counters; see
+/// [`RtpIncomingSourceGroup::get_stats`].
+pub type IncomingSourceGroupStats = bridge::IncomingSourceGroupStats;
+
+/// Connection-wide counters and transport state; see
+/// [`RtpBundleTransportConnection::get_stats`].
+#[derive(Debug, Copy, Clone)]
+pub struct ConnectionStats {
+    pub dtls_state: DtlsState,
+    pub ice_connected: bool,
+    pub round_trip_time_ms: f64,
+}
+
+impl From<bridge::ConnectionStats> for ConnectionStats {
+    fn from(stats: bridge::ConnectionStats) -> Self {
+        ConnectionStats {
+            dtls_state: DtlsState::from(stats.dtls_state),
+            ice_connected: stats.ice_connected,
+            round_trip_time_ms: stats.round_trip_time_ms,
+        }
+    }
+}
+
+#[allow(unused_variables)]
+pub trait DtlsIceTransportListener: Send {
+    fn on_ice_timeout(&mut self) {}
+    fn on_dtls_state_changed(&mut self, state: DtlsState) {}
+    fn on_remote_ice_candidate_activated(&mut self, ip: &str, port: u16, priority: u32) {}
+    fn on_transport_wide_cc_feedback(&mut self, feedback: Vec<TransportWideCcPacketFeedback>) {}
+}
+
+struct DtlsIceTransportListenerAdapter(Box<dyn DtlsIceTransportListener>);
+
+impl bridge::DtlsIceTransportListener for DtlsIceTransportListenerAdapter {
+    fn on_ice_timeout(&mut self) {
+        self.0.on_ice_timeout()
+    }
 
-pub type DtlsIceTransportDtlsState = bridge::DtlsIceTransportDtlsState;
+    fn on_dtls_state_changed(&mut self, state: bridge::DtlsIceTransportDtlsState) {
+        self.0.on_dtls_state_changed(DtlsState::from(state))
+    }
+
+    fn on_remote_ice_candidate_activated(&mut self, ip: &str, port: u16, priority: u32) {
+        self.0.on_remote_ice_candidate_activated(ip, port, priority)
+    }
+
+    fn on_transport_wide_cc_feedback(&mut self, feedback: Vec<bridge::TransportWideCcPacketFeedback>) {
+        self.0.on_transport_wide_cc_feedback(feedback)
+    }
+}
 
 pub type MediaFrameType = bridge::MediaFrameType;
 
 pub struct RtpIncomingSourceGroup(cxx::UniquePtr<bridge::RtpIncomingSourceGroupFacade>);
 
+impl RtpIncomingSourceGroup {
+    pub fn get_stats(&self) -> IncomingSourceGroupStats {
+        self.0.get_stats()
+    }
+}
+
 pub struct RtpOutgoingSourceGroup(cxx::UniquePtr<bridge::RtpOutgoingSourceGroupFacade>);
 
 impl RtpOutgoingSourceGroup {
@@ -122,16 +421,29 @@ impl RtpStreamTransponder {
     pub fn set_incoming(&mut self, incoming: &mut RtpIncomingSourceGroup) {
         self.0.pin_mut().set_incoming(incoming.0.pin_mut());
     }
+
+    /// Caps the bitrate this transponder forwards, e.g. to the target
+    /// produced by a [`crate::congestion::BandwidthEstimator`].
+    pub fn set_target_bitrate(&mut self, bitrate_bps: u32) {
+        self.0.pin_mut().set_target_bitrate(bitrate_bps);
+    }
 }
 
 pub struct RtpBundleTransportConnection(cxx::UniquePtr<bridge::RtpBundleTransportConnectionFacade>);
 
 impl RtpBundleTransportConnection {
     pub fn set_listener(&mut self, listener: impl DtlsIceTransportListener + 'static) {
-        let listener = bridge::DtlsIceTransportListenerRustAdapter::from(listener);
+        let adapter = DtlsIceTransportListenerAdapter(Box::new(listener));
+        let listener = bridge::DtlsIceTransportListenerRustAdapter::from(adapter);
         self.0.pin_mut().set_listener(Box::new(listener));
     }
 
+    /// Selects which side of the DTLS handshake this connection plays; see
+    /// [`Role`] for how that maps onto `a=setup:`.
+    pub fn set_dtls_role(&mut self, role: Role) {
+        self.0.pin_mut().set_dtls_role(role.into());
+    }
+
     pub fn set_remote_properties(&mut self, properties: &Properties) {
         self.0.pin_mut().set_remote_properties(&properties.0);
     }
@@ -140,6 +452,15 @@ impl RtpBundleTransportConnection {
         self.0.pin_mut().set_local_properties(&properties.0);
     }
 
+    /// Pins the expected remote peer certificate fingerprint; at DTLS
+    /// handshake completion the presented certificate is hashed with `hash`
+    /// and compared against `value`, reporting a mismatch as
+    /// [`DtlsState::Failed`] through the connection's
+    /// listener rather than silently accepting an unverified peer.
+    pub fn set_remote_fingerprint(&mut self, hash: DtlsConnectionHash, value: &str) {
+        self.0.pin_mut().set_remote_fingerprint(hash.into(), value);
+    }
+
     pub fn add_incoming_source_group(
         &mut self,
         kind: MediaFrameType,
@@ -174,8 +495,46 @@ impl RtpBundleTransportConnection {
         Ok(RtpOutgoingSourceGroup(outgoing_source_group))
     }
 
-    pub fn add_remote_candidate(&mut self, ip: &str, port: u16) {
-        self.0.pin_mut().add_remote_candidate(ip, port);
+    /// Applies a single remote ICE candidate, gathered up front from the
+    /// initial offer/answer or trickled in afterwards over the signalling
+    /// channel. `related_ip`/`related_port` are the `raddr`/`rport`
+    /// extension-attributes SDP carries for server-reflexive and relayed
+    /// candidates; pass `None` for a host candidate, which has no base to
+    /// report.
+    pub fn add_remote_candidate(
+        &mut self,
+        ip: &str,
+        port: u16,
+        transport: &IceTransportType,
+        kind: &IceCandidateType,
+        priority: u32,
+        related_ip: Option<&str>,
+        related_port: Option<u16>,
+    ) {
+        self.0.pin_mut().add_remote_candidate(
+            ip,
+            port,
+            transport.into(),
+            kind.into(),
+            priority,
+            related_ip.unwrap_or(""),
+            related_port.unwrap_or(0),
+        );
+    }
+
+    /// The DTLS-SRTP profile the handshake settled on, or `None` before the
+    /// handshake completes (or if the native library reports a name we don't
+    /// recognize).
+    pub fn negotiated_srtp_protection_profile(&self) -> Option<SrtpProtectionProfile> {
+        let profile = self.0.get_negotiated_srtp_protection_profile();
+        SrtpProtectionProfile::from_str(&profile).ok()
+    }
+
+    /// Connection-wide counters and transport state; call
+    /// [`RtpIncomingSourceGroup::get_stats`] on each track for per-track
+    /// counters.
+    pub fn get_stats(&self) -> ConnectionStats {
+        self.0.get_stats().into()
     }
 }
 
@@ -192,6 +551,19 @@ impl RtpBundleTransport {
         self.0.get_local_port()
     }
 
+    /// Gathers the local ICE candidates for this transport's `get_local_port`:
+    /// a host candidate per non-loopback local interface, plus a
+    /// server-reflexive and/or relay candidate when `stun_server`/
+    /// `turn_server` are given. See [`ice::gather_local_candidates`].
+    pub fn gather_local_candidates(
+        &self,
+        component: u16,
+        stun_server: Option<std::net::SocketAddr>,
+        turn_server: Option<&turn::TurnServerConfig>,
+    ) -> Result<ice::GatheredCandidates> {
+        ice::gather_local_candidates(self.get_local_port(), component, stun_server, turn_server)
+    }
+
     pub fn add_ice_transport(
         &mut self,
         username: &str,
@@ -0,0 +1,391 @@
+//! Parses a WebRTC SDP offer and drives [`RtpBundleTransport`]/
+//! [`RtpBundleTransportConnection`] to answer it, so callers don't have to
+//! hand-roll `set_remote_properties`/`set_local_properties`,
+//! `add_incoming_source_group`/`add_outgoing_source_group` and
+//! `add_remote_candidate` just to terminate a browser `PeerConnection`.
+//!
+//! This is deliberately not a general-purpose SDP library: it only keeps the
+//! handful of session/media attributes needed to drive the native API.
+//!
+//! Callers that want the fuller JSEP-style model instead (e.g. for WHIP or
+//! trickle ICE) reach it as `crate::sdp::webrtc`/`enums`/`types`/`attributes`,
+//! re-exported here from `semantic_sdp` rather than duplicated.
+
+pub use semantic_sdp::{attributes, enums, types, webrtc};
+
+use attributes::BaseAttribute;
+
+use crate::{
+    get_certificate_fingerprint, DtlsConnectionHash, MediaFrameType, Properties, Result, RtpBundleTransport,
+    RtpBundleTransportConnection,
+};
+
+#[derive(Debug, Clone)]
+pub struct OfferedCodec {
+    pub payload_type: u8,
+    pub name: String,
+    pub clock_rate: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct OfferedMedia {
+    pub kind: MediaFrameType,
+    pub mid: Option<String>,
+    pub codecs: Vec<OfferedCodec>,
+    pub extensions: Vec<(u8, String)>,
+    pub media_ssrc: Option<u32>,
+    pub rtx_ssrc: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Offer {
+    pub ice_ufrag: Option<String>,
+    pub ice_pwd: Option<String>,
+    pub setup: Option<String>,
+    pub fingerprint_hash: Option<String>,
+    pub fingerprint_value: Option<String>,
+    pub media: Vec<OfferedMedia>,
+    pub candidates: Vec<attributes::Candidate>,
+}
+
+/// Splits a `\r\n`-or-`\n`-delimited SDP body into its `x=value` lines.
+fn lines(sdp: &str) -> impl Iterator<Item = (u8, &str)> {
+    sdp.lines().filter_map(|line| {
+        let line = line.trim_end_matches('\r');
+        let bytes = line.as_bytes();
+        if bytes.len() < 2 || bytes[1] != b'=' {
+            return None;
+        }
+        Some((bytes[0], &line[2..]))
+    })
+}
+
+fn parse_attribute(line: &str) -> (&str, Option<&str>) {
+    match line.find(':') {
+        Some(pos) => (&line[..pos], Some(&line[pos + 1..])),
+        None => (line, None),
+    }
+}
+
+fn media_kind(value: &str) -> MediaFrameType {
+    match value {
+        "audio" => MediaFrameType::Audio,
+        "video" => MediaFrameType::Video,
+        _ => MediaFrameType::Unknown,
+    }
+}
+
+fn parse_rtpmap(value: &str) -> Option<(u8, OfferedCodec)> {
+    let mut fields = value.splitn(2, ' ');
+    let payload_type: u8 = fields.next()?.parse().ok()?;
+    let mut name_and_clock = fields.next()?.split('/');
+    let name = name_and_clock.next()?.to_owned();
+    let clock_rate = name_and_clock.next()?.parse().ok()?;
+
+    Some((
+        payload_type,
+        OfferedCodec {
+            payload_type,
+            name,
+            clock_rate,
+        },
+    ))
+}
+
+fn parse_extmap(value: &str) -> Option<(u8, String)> {
+    let mut fields = value.splitn(2, ' ');
+    let id: u8 = fields.next()?.parse().ok()?;
+    let uri = fields.next()?.to_owned();
+    Some((id, uri))
+}
+
+/// `a=ssrc-group:FID <media-ssrc> <rtx-ssrc>`, the only grouping semantic we
+/// need: it tells us which of the `a=ssrc:` lines is the RTX stream for
+/// which primary stream.
+fn parse_ssrc_group(value: &str, media: &mut OfferedMedia) {
+    let mut fields = value.split(' ');
+    if fields.next() != Some("FID") {
+        return;
+    }
+
+    media.media_ssrc = fields.next().and_then(|s| s.parse().ok());
+    media.rtx_ssrc = fields.next().and_then(|s| s.parse().ok());
+}
+
+/// Parses an `a=candidate:` line's value into the fully structured
+/// `semantic_sdp` representation (foundation, component, transport, priority,
+/// `typ`, and the optional `raddr`/`rport`), via its stable byte-oriented
+/// entry point rather than hand-rolling another RFC 5245 parser in this file.
+/// This is what lets [`negotiate`] forward trickled candidates on to
+/// `add_remote_candidate` with their real transport/type/priority instead of
+/// just a bare address and port.
+fn parse_candidate(value: &str) -> Option<attributes::Candidate> {
+    let line = format!("a=candidate:{}", value);
+    let (_, attribute, _) = attributes::parse_attribute_bytes(line.as_bytes()).ok()?;
+    attribute.as_any().downcast_ref::<attributes::Candidate>().cloned()
+}
+
+impl Offer {
+    /// Parses just enough of an SDP offer to answer it: the session-level
+    /// ICE/DTLS attributes, one [`OfferedMedia`] per `m=` line, and the
+    /// offered `a=candidate:` lines.
+    pub fn parse(sdp: &str) -> std::result::Result<Offer, String> {
+        let mut offer = Offer::default();
+        let mut current: Option<OfferedMedia> = None;
+
+        for (kind, value) in lines(sdp) {
+            match kind {
+                b'm' => {
+                    if let Some(media) = current.take() {
+                        offer.media.push(media);
+                    }
+
+                    let media_type = value.split(' ').next().ok_or("m= line missing media type")?;
+
+                    current = Some(OfferedMedia {
+                        kind: media_kind(media_type),
+                        mid: None,
+                        codecs: Vec::new(),
+                        extensions: Vec::new(),
+                        media_ssrc: None,
+                        rtx_ssrc: None,
+                    });
+                }
+                b'a' => {
+                    let (name, attribute_value) = parse_attribute(value);
+
+                    match name {
+                        "ice-ufrag" => offer.ice_ufrag = attribute_value.map(|s| s.to_owned()),
+                        "ice-pwd" => offer.ice_pwd = attribute_value.map(|s| s.to_owned()),
+                        "setup" => offer.setup = attribute_value.map(|s| s.to_owned()),
+                        "fingerprint" => {
+                            if let Some(value) = attribute_value {
+                                let mut parts = value.splitn(2, ' ');
+                                offer.fingerprint_hash = parts.next().map(|s| s.to_owned());
+                                offer.fingerprint_value = parts.next().map(|s| s.to_owned());
+                            }
+                        }
+                        "mid" => {
+                            if let Some(media) = current.as_mut() {
+                                media.mid = attribute_value.map(|s| s.to_owned());
+                            }
+                        }
+                        "rtpmap" => {
+                            if let Some(media) = current.as_mut() {
+                                if let Some((_, codec)) = parse_rtpmap(attribute_value.unwrap_or_default()) {
+                                    media.codecs.push(codec);
+                                }
+                            }
+                        }
+                        "extmap" => {
+                            if let Some(media) = current.as_mut() {
+                                if let Some(extension) = parse_extmap(attribute_value.unwrap_or_default()) {
+                                    media.extensions.push(extension);
+                                }
+                            }
+                        }
+                        "ssrc-group" => {
+                            if let Some(media) = current.as_mut() {
+                                if let Some(value) = attribute_value {
+                                    parse_ssrc_group(value, media);
+                                }
+                            }
+                        }
+                        "ssrc" => {
+                            if let Some(media) = current.as_mut() {
+                                if media.media_ssrc.is_none() {
+                                    media.media_ssrc = attribute_value.and_then(|v| v.split(' ').next()).and_then(|s| s.parse().ok());
+                                }
+                            }
+                        }
+                        "candidate" => {
+                            if let Some(value) = attribute_value {
+                                if let Some(candidate) = parse_candidate(value) {
+                                    offer.candidates.push(candidate);
+                                }
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(media) = current.take() {
+            offer.media.push(media);
+        }
+
+        Ok(offer)
+    }
+}
+
+fn media_property_prefix(media: &OfferedMedia, index: usize) -> String {
+    media.mid.clone().unwrap_or_else(|| format!("media{}", index))
+}
+
+/// A cheap, deterministic stand-in for a random SSRC generator: all we
+/// actually need is "stable for this connection, distinct between media
+/// sections".
+fn generate_ssrc(seed: &str) -> u32 {
+    let mut hash: u32 = 2166136261;
+    for byte in seed.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(16777619);
+    }
+    hash | 1
+}
+
+/// Negotiates an answer for `offer` against a freshly created ICE transport
+/// on `transport`, setting up the remote/local `Properties` codec tables, an
+/// outgoing source group per offered `m=` section (plus an incoming one for
+/// each section that lists a remote SSRC), and feeding every offered
+/// `a=candidate:` line into `add_remote_candidate`.
+///
+/// `local_username` is combined with the offer's `ice-ufrag` exactly like
+/// `RtpBundleTransport::add_ice_transport` already expects; `local_pwd` is
+/// the ICE password we'll answer with.
+pub fn negotiate(
+    transport: &mut RtpBundleTransport,
+    local_username: &str,
+    local_pwd: &str,
+    offer: &Offer,
+) -> Result<(RtpBundleTransportConnection, String)> {
+    let ice_ufrag = offer.ice_ufrag.as_deref().ok_or("offer is missing ice-ufrag")?;
+    let ice_pwd = offer.ice_pwd.as_deref().ok_or("offer is missing ice-pwd")?;
+    let offered_setup = offer.setup.as_deref().ok_or("offer is missing a=setup")?;
+    offer.fingerprint_hash.as_deref().ok_or("offer is missing a=fingerprint")?;
+    offer.fingerprint_value.as_deref().ok_or("offer is missing a=fingerprint")?;
+
+    let local_fingerprint = get_certificate_fingerprint(DtlsConnectionHash::Sha256)?;
+    let answer_setup = match offered_setup {
+        "active" => "passive",
+        "passive" => "active",
+        _ => "active",
+    };
+
+    let mut ice_properties = Properties::new();
+    ice_properties.set_string("ice.localUsername", local_username);
+    ice_properties.set_string("ice.localPassword", local_pwd);
+    ice_properties.set_string("ice.remoteUsername", ice_ufrag);
+    ice_properties.set_string("ice.remotePassword", ice_pwd);
+    ice_properties.set_string("dtls.setup", answer_setup);
+    ice_properties.set_string("dtls.hash", "SHA-256");
+    ice_properties.set_string("dtls.fingerprint", &local_fingerprint);
+    ice_properties.set_bool("disableSTUNKeepAlive", false);
+    ice_properties.set_string("srtpProtectionProfiles", "");
+
+    let username = format!("{}:{}", local_username, ice_ufrag);
+    let mut connection = transport.add_ice_transport(&username, &ice_properties)?;
+
+    let mut codec_properties = Properties::new();
+    for (index, media) in offer.media.iter().enumerate() {
+        let prefix = media_property_prefix(media, index);
+
+        for (codec_index, codec) in media.codecs.iter().enumerate() {
+            codec_properties.set_string(&format!("{}.codecs.{}.codec", prefix, codec_index), &codec.name);
+            codec_properties.set_int(&format!("{}.codecs.{}.type", prefix, codec_index), codec.payload_type as i32);
+            codec_properties.set_int(&format!("{}.codecs.{}.rate", prefix, codec_index), codec.clock_rate as i32);
+        }
+
+        for (id, uri) in &media.extensions {
+            codec_properties.set_string(&format!("{}.ext.{}", prefix, id), uri);
+        }
+    }
+
+    connection.set_remote_properties(&codec_properties);
+    connection.set_local_properties(&codec_properties);
+
+    let mut answer_media_ssrcs = Vec::with_capacity(offer.media.len());
+
+    for (index, media) in offer.media.iter().enumerate() {
+        let prefix = media_property_prefix(media, index);
+
+        if let Some(media_ssrc) = media.media_ssrc {
+            connection.add_incoming_source_group(media.kind, media.mid.as_deref(), None, Some(media_ssrc), media.rtx_ssrc)?;
+        }
+
+        let local_media_ssrc = generate_ssrc(&format!("{}:{}:out", username, prefix));
+        let local_rtx_ssrc = generate_ssrc(&format!("{}:{}:rtx", username, prefix));
+        connection.add_outgoing_source_group(media.kind, media.mid.as_deref(), local_media_ssrc, Some(local_rtx_ssrc))?;
+
+        answer_media_ssrcs.push((media.mid.clone(), local_media_ssrc, local_rtx_ssrc));
+    }
+
+    for candidate in &offer.candidates {
+        connection.add_remote_candidate(
+            &candidate.address,
+            candidate.port,
+            &candidate.transport,
+            &candidate.kind,
+            candidate.priority,
+            candidate.rel_addr.as_deref(),
+            candidate.rel_port,
+        );
+    }
+
+    let answer = render_answer(
+        &local_fingerprint,
+        answer_setup,
+        local_username,
+        local_pwd,
+        &offer.media,
+        &answer_media_ssrcs,
+    );
+
+    Ok((connection, answer))
+}
+
+fn render_answer(
+    local_fingerprint: &str,
+    answer_setup: &str,
+    local_ufrag: &str,
+    local_pwd: &str,
+    medias: &[OfferedMedia],
+    answer_media_ssrcs: &[(Option<String>, u32, u32)],
+) -> String {
+    let mut sdp = String::new();
+
+    sdp.push_str("v=0\r\n");
+    sdp.push_str("o=- 0 0 IN IP4 0.0.0.0\r\n");
+    sdp.push_str("s=-\r\n");
+    sdp.push_str("t=0 0\r\n");
+
+    for (media, (mid, media_ssrc, rtx_ssrc)) in medias.iter().zip(answer_media_ssrcs) {
+        let kind = match media.kind {
+            MediaFrameType::Audio => "audio",
+            MediaFrameType::Video => "video",
+            _ => "application",
+        };
+
+        let formats = media
+            .codecs
+            .iter()
+            .map(|codec| codec.payload_type.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        sdp.push_str(&format!("m={} 9 UDP/TLS/RTP/SAVPF {}\r\n", kind, formats));
+        sdp.push_str("c=IN IP4 0.0.0.0\r\n");
+        sdp.push_str(&format!("a=ice-ufrag:{}\r\n", local_ufrag));
+        sdp.push_str(&format!("a=ice-pwd:{}\r\n", local_pwd));
+        sdp.push_str(&format!("a=setup:{}\r\n", answer_setup));
+        sdp.push_str(&format!("a=fingerprint:sha-256 {}\r\n", local_fingerprint));
+
+        if let Some(mid) = mid {
+            sdp.push_str(&format!("a=mid:{}\r\n", mid));
+        }
+
+        sdp.push_str("a=sendrecv\r\n");
+
+        for codec in &media.codecs {
+            sdp.push_str(&format!("a=rtpmap:{} {}/{}\r\n", codec.payload_type, codec.name, codec.clock_rate));
+        }
+
+        sdp.push_str(&format!("a=ssrc:{} cname:-\r\n", media_ssrc));
+        sdp.push_str(&format!("a=ssrc:{} cname:-\r\n", rtx_ssrc));
+        sdp.push_str(&format!("a=ssrc-group:FID {} {}\r\n", media_ssrc, rtx_ssrc));
+    }
+
+    sdp
+}
@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use semantic_sdp::attributes::parse_attribute_bytes;
+
+// Two invariants: parsing never panics on arbitrary bytes, and for any
+// attribute whose `to_string()` gives us a value back, re-parsing the line we
+// would write out for it must round-trip to the same thing. This is meant to
+// catch asymmetries between `parse` and `to_string`, e.g. in the optional
+// fields of `RtpMap`, `Candidate`, `Fingerprint`, `Rtcp`, etc.
+fuzz_target!(|data: &[u8]| {
+    let Ok((name, attribute, _rest)) = parse_attribute_bytes(data) else {
+        return;
+    };
+
+    let Some(value) = attribute.to_string() else {
+        return;
+    };
+
+    let line = format!("a={}:{}\r\n", name, value);
+    let (round_tripped_name, round_tripped, _) =
+        parse_attribute_bytes(line.as_bytes()).expect("re-parsing our own serialization must succeed");
+
+    assert_eq!(name, round_tripped_name);
+    assert_eq!(format!("{:?}", attribute), format!("{:?}", round_tripped));
+});
@@ -1,6 +1,7 @@
+use nom::error::{ContextError, FromExternalError, ParseError};
 use ordered_multimap::ListOrderedMultimap;
 
-use crate::attributes::{parse_attribute, NamedAttribute, ParsableAttribute};
+use crate::attributes::{parse_attribute_with_registry, AttributeRegistry, NamedAttribute, ParsableAttribute};
 
 // TODO: Might make sense to use smallvec here
 // TODO: Box<dyn> is working out well, but it'd be good to look at the enum approach again
@@ -41,6 +42,38 @@ impl<'a> IntoIterator for &'a AttributeMap {
     }
 }
 
+// AttributeMap holds `Box<dyn ParsableAttribute>`, so `Serialize`/`Deserialize`
+// can't be derived; we round-trip through the same (name, value) shape the
+// `Display` impl above renders, reusing `append_unknown` to parse each entry
+// back into its typed representation (or fall back to an unknown attribute)
+// the same way `append_unknown_with_registry` already does.
+#[cfg(feature = "serde")]
+impl serde::Serialize for AttributeMap {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.into_iter().map(|(name, attribute)| (name, attribute.to_string())))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AttributeMap {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let entries: Vec<(String, Option<String>)> = serde::Deserialize::deserialize(deserializer)?;
+
+        let mut map = Self::new();
+        for (name, value) in entries {
+            map.append_unknown(&name, value).map_err(serde::de::Error::custom)?;
+        }
+
+        Ok(map)
+    }
+}
+
 impl AttributeMap {
     pub fn new() -> Self {
         Self(ListOrderedMultimap::new())
@@ -58,6 +91,26 @@ impl AttributeMap {
 
     // We just use String as the result type to avoid exposing the nom trait soup publicly
     pub fn append_unknown(&mut self, name: &str, value: Option<String>) -> Result<(), String> {
+        self.append_unknown_with_registry(&AttributeRegistry::with_builtins(), name, value)
+    }
+
+    /// Like [`append_unknown`](Self::append_unknown), but consults `registry`
+    /// instead of only the built-in attribute types, so an attribute
+    /// registered with [`AttributeRegistry::register`] surfaces from
+    /// [`get`](Self::get)/[`get_vec`](Self::get_vec) as its typed value
+    /// instead of falling through to [`get_unknown`](Self::get_unknown).
+    pub fn append_unknown_with_registry<'a, E>(
+        &mut self,
+        registry: &AttributeRegistry<'a, E>,
+        name: &str,
+        value: Option<String>,
+    ) -> Result<(), String>
+    where
+        E: ParseError<&'a str>
+            + ContextError<&'a str>
+            + FromExternalError<&'a str, crate::EnumParseError>
+            + FromExternalError<&'a str, std::num::ParseIntError>,
+    {
         let name = name.to_ascii_lowercase();
 
         // This is quite gross, but we appear to need it for safety. We could bypass it
@@ -69,7 +122,7 @@ impl AttributeMap {
             None => "".to_owned(),
         };
 
-        let (_, attribute) = parse_attribute(&name, &value).map_err(|e| match e {
+        let (_, attribute) = parse_attribute_with_registry(&name, &value, registry).map_err(|e| match e {
             nom::Err::Error(e) | nom::Err::Failure(e) => nom::error::convert_error(value.as_str(), e),
             nom::Err::Incomplete(_) => unreachable!(),
         })?;
@@ -1,10 +1,19 @@
+//! Typed [`ParsableAttribute`] implementations for individual `a=` lines,
+//! including the full media-level RTP attribute set: `a=rtpmap`
+//! ([`RtpMap`]), `a=fmtp` ([`FormatParameters`]), `a=rtcp-fb`
+//! ([`RtcpFeedback`]), `a=extmap` ([`ExtensionMap`]), `a=ssrc`
+//! ([`SsrcAttribute`]), and `a=ssrc-group` ([`SsrcGroup`]). These flow into
+//! [`crate::webrtc::RtpMediaDescription::from_sdp`], which is where the raw
+//! per-attribute values get cross-referenced against each other and
+//! resolved into payloads/encodings.
+
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use nom::bytes::complete::{tag_no_case, take_till1};
 use nom::character::complete::{char, hex_digit1, not_line_ending};
-use nom::combinator::{map_res, opt};
+use nom::combinator::{map_res, opt, verify};
 use nom::error::{ContextError, FromExternalError, ParseError};
 use nom::multi::{many0, many1, separated_list1};
 use nom::sequence::{preceded, separated_pair};
@@ -12,6 +21,10 @@ use nom::sequence::{preceded, separated_pair};
 use crate::enums::*;
 use crate::{field_separator, line_ending_or_eof, value_field};
 
+/// Parses a named attribute using a fresh [`AttributeRegistry`] pre-populated
+/// with the attributes this crate knows about. See
+/// [`parse_attribute_with_registry`] to also recognize additional, caller
+/// registered attribute types.
 pub(crate) fn parse_attribute<'a, E>(name: &str, input: &'a str) -> nom::IResult<&'a str, Box<dyn ParsableAttribute>, E>
 where
     E: ParseError<&'a str>
@@ -19,42 +32,148 @@ where
         + FromExternalError<&'a str, crate::EnumParseError>
         + FromExternalError<&'a str, std::num::ParseIntError>,
 {
-    let (input, attribute) = match name {
-        BundleOnly::NAME => BundleOnly::parse_boxed(input),
-        Candidate::NAME => Candidate::parse_boxed(input),
-        EndOfCandidates::NAME => EndOfCandidates::parse_boxed(input),
-        ExtensionMap::NAME => ExtensionMap::parse_boxed(input),
-        ExtensionMapAllowMixed::NAME => ExtensionMapAllowMixed::parse_boxed(input),
-        Fingerprint::NAME => Fingerprint::parse_boxed(input),
-        FormatParameters::NAME => FormatParameters::parse_boxed(input),
-        Group::NAME => Group::parse_boxed(input),
-        IceLite::NAME => IceLite::parse_boxed(input),
-        IceOptions::NAME => IceOptions::parse_boxed(input),
-        IcePwd::NAME => IcePwd::parse_boxed(input),
-        IceUfrag::NAME => IceUfrag::parse_boxed(input),
-        Inactive::NAME => Inactive::parse_boxed(input),
-        MaxPacketTime::NAME => MaxPacketTime::parse_boxed(input),
-        MediaStreamId::NAME => MediaStreamId::parse_boxed(input),
-        MediaStreamIdSemantic::NAME => MediaStreamIdSemantic::parse_boxed(input),
-        Mid::NAME => Mid::parse_boxed(input),
-        PacketTime::NAME => PacketTime::parse_boxed(input),
-        ReceiveOnly::NAME => ReceiveOnly::parse_boxed(input),
-        Rtcp::NAME => Rtcp::parse_boxed(input),
-        RtcpFeedback::NAME => RtcpFeedback::parse_boxed(input),
-        RtcpMux::NAME => RtcpMux::parse_boxed(input),
-        RtcpReducedSize::NAME => RtcpReducedSize::parse_boxed(input),
-        RtpMap::NAME => RtpMap::parse_boxed(input),
-        SendOnly::NAME => SendOnly::parse_boxed(input),
-        SendReceive::NAME => SendReceive::parse_boxed(input),
-        Setup::NAME => Setup::parse_boxed(input),
-        SsrcAttribute::NAME => SsrcAttribute::parse_boxed(input),
-        SsrcGroup::NAME => SsrcGroup::parse_boxed(input),
-        _ => Option::<String>::parse_boxed(input),
-    }?;
+    parse_attribute_with_registry(name, input, &AttributeRegistry::with_builtins())
+}
+
+/// Like [`parse_attribute`], but consults `registry` instead of always
+/// building a fresh built-ins-only one, so callers that have registered their
+/// own [`ParsableAttribute`] implementations (vendor `a=x-google-*` lines,
+/// `a=rid`, proprietary attributes, …) get them back as typed values instead
+/// of falling through to the opaque [`Option<String>`] case.
+pub(crate) fn parse_attribute_with_registry<'a, E>(
+    name: &str,
+    input: &'a str,
+    registry: &AttributeRegistry<'a, E>,
+) -> nom::IResult<&'a str, Box<dyn ParsableAttribute>, E>
+where
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, crate::EnumParseError>
+        + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    let (input, attribute) = match registry.lookup(name) {
+        Some(parser) => parser(input)?,
+        None => Option::<String>::parse_boxed(input)?,
+    };
 
     Ok((input, attribute))
 }
 
+/// Parses a single raw `a=<name>[:value]` attribute line and returns its name,
+/// the typed attribute, and any bytes left over after it (normally empty,
+/// since each line is expected to be consumed in full). This is the stable,
+/// public entry point for callers that only have bytes off the wire — e.g. a
+/// fuzz harness, or a caller that doesn't want to deal with the nom
+/// generic-error soup the rest of this crate's parsing is built on.
+pub fn parse_attribute_bytes(line: &[u8]) -> Result<(String, Box<dyn ParsableAttribute>, &[u8]), String> {
+    let line = std::str::from_utf8(line).map_err(|e| e.to_string())?;
+    let line = line
+        .strip_prefix("a=")
+        .ok_or_else(|| "attribute line must start with \"a=\"".to_owned())?;
+
+    let split_at = line.find(|c| c == ':' || c == '\r' || c == '\n').unwrap_or(line.len());
+    let name = line[..split_at].to_ascii_lowercase();
+    let value = &line[split_at..];
+
+    let (rest, attribute) =
+        parse_attribute::<nom::error::VerboseError<&str>>(&name, value).map_err(|e| match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => nom::error::convert_error(value, e),
+            nom::Err::Incomplete(_) => unreachable!(),
+        })?;
+
+    Ok((name, attribute, rest.as_bytes()))
+}
+
+type AttributeParser<'a, E> = fn(&'a str) -> nom::IResult<&'a str, Box<dyn ParsableAttribute>, E>;
+
+/// Maps attribute names to the parser for their [`ParsableAttribute`] type, so
+/// downstream users can register their own implementations (e.g. vendor
+/// `a=x-google-*` lines, `a=rid`, proprietary attributes) and have
+/// [`parse_attribute_with_registry`] surface them as typed values instead of
+/// falling through to the opaque [`Option<String>`] case.
+/// [`AttributeRegistry::with_builtins`] pre-populates the attributes this
+/// crate knows about natively.
+///
+/// This is built per-call rather than cached behind a `once_cell`/`lazy_static`
+/// global: the stored function pointers are monomorphized over the nom error
+/// type `E`, which varies by caller (a fuzz harness wants `VerboseError`, most
+/// callers want the cheaper error-kind tuple), so there's no single `E` a
+/// process-wide static could be generic over.
+pub struct AttributeRegistry<'a, E>(HashMap<&'static str, AttributeParser<'a, E>>);
+
+impl<'a, E> AttributeRegistry<'a, E>
+where
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, crate::EnumParseError>
+        + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register::<BundleOnly>();
+        registry.register::<Candidate>();
+        registry.register::<EndOfCandidates>();
+        registry.register::<ExtensionMap>();
+        registry.register::<ExtensionMapAllowMixed>();
+        registry.register::<Fingerprint>();
+        registry.register::<FormatParameters>();
+        registry.register::<Group>();
+        registry.register::<IceLite>();
+        registry.register::<IceOptions>();
+        registry.register::<IcePwd>();
+        registry.register::<IceUfrag>();
+        registry.register::<Inactive>();
+        registry.register::<MaxMessageSize>();
+        registry.register::<MaxPacketTime>();
+        registry.register::<MediaStreamId>();
+        registry.register::<MediaStreamIdSemantic>();
+        registry.register::<Mid>();
+        registry.register::<PacketTime>();
+        registry.register::<ReceiveOnly>();
+        registry.register::<Rid>();
+        registry.register::<Rtcp>();
+        registry.register::<RtcpFeedback>();
+        registry.register::<RtcpMux>();
+        registry.register::<RtcpReducedSize>();
+        registry.register::<RtpMap>();
+        registry.register::<SctpPort>();
+        registry.register::<SendOnly>();
+        registry.register::<SendReceive>();
+        registry.register::<Setup>();
+        registry.register::<Simulcast>();
+        registry.register::<SsrcAttribute>();
+        registry.register::<SsrcGroup>();
+        registry
+    }
+
+    /// Registers `T` so [`parse_attribute_with_registry`] returns it as a
+    /// typed value for `T::NAME`, instead of falling through to the
+    /// [`Option<String>`] case.
+    pub fn register<T: NamedAttribute>(&mut self) {
+        self.0.insert(T::NAME, T::parse_boxed::<E>);
+    }
+
+    fn lookup(&self, name: &str) -> Option<AttributeParser<'a, E>> {
+        self.0.get(name).copied()
+    }
+}
+
+impl<'a, E> Default for AttributeRegistry<'a, E>
+where
+    E: ParseError<&'a str>
+        + ContextError<&'a str>
+        + FromExternalError<&'a str, crate::EnumParseError>
+        + FromExternalError<&'a str, std::num::ParseIntError>,
+{
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
 pub trait BaseAttribute: Any {
     fn as_any(&self) -> &dyn Any;
 }
@@ -281,14 +400,88 @@ impl ParsableAttribute for RtpMap {
 }
 
 // RFC 4566
+/// The `a=fmtp` parameters for a payload type.
+///
+/// Most codecs use the common `;`-separated `key=value` form (e.g. H.264's
+/// `packetization-mode=1`), which is parsed into an ordered list of key/value
+/// pairs accessible through [`get`](Self::get)/[`set`](Self::set). A few
+/// codecs don't (e.g. `telephone-event`'s payload ranges like `0-15`); for
+/// those the raw string is kept as-is and [`parameters`](Self::parameters)
+/// returns `None`.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct FormatParameters {
     pub payload: u8,
-    pub parameters: String,
+    raw: String,
+    parameters: Option<Vec<(String, Option<String>)>>,
 }
 
 impl_value_sdp_attribute!("fmtp", FormatParameters);
 
+impl FormatParameters {
+    pub fn new(payload: u8) -> Self {
+        FormatParameters {
+            payload,
+            raw: String::new(),
+            parameters: Some(Vec::new()),
+        }
+    }
+
+    fn parse_parameters(raw: &str) -> Option<Vec<(String, Option<String>)>> {
+        if !raw.contains(';') && !raw.contains('=') {
+            return None;
+        }
+
+        Some(
+            raw.split(';')
+                .map(|parameter| match parameter.trim().split_once('=') {
+                    Some((key, value)) => (key.trim().to_owned(), Some(value.trim().to_owned())),
+                    None => (parameter.trim().to_owned(), None),
+                })
+                .collect(),
+        )
+    }
+
+    /// Iterates the structured parameters in declaration order, or `None` if
+    /// the raw string isn't in the common `;`-separated `key=value` form.
+    pub fn parameters(&self) -> Option<impl Iterator<Item = (&str, Option<&str>)>> {
+        self.parameters
+            .as_deref()
+            .map(|parameters| parameters.iter().map(|(key, value)| (key.as_str(), value.as_deref())))
+    }
+
+    /// Returns the value of `key`. `None` both when `key` is absent and when
+    /// it's a bare token with no `=value`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.parameters()?.find(|(k, _)| *k == key).and_then(|(_, v)| v)
+    }
+
+    /// Sets `key` to `value`. If the raw string wasn't in the common form, the
+    /// structured list starts out empty, so the raw value is no longer
+    /// serialized.
+    pub fn set(&mut self, key: &str, value: Option<String>) {
+        let parameters = self.parameters.get_or_insert_with(Vec::new);
+
+        match parameters.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value,
+            None => parameters.push((key.to_owned(), value)),
+        }
+    }
+
+    /// Removes `key`, if present.
+    pub fn remove(&mut self, key: &str) {
+        if let Some(parameters) = &mut self.parameters {
+            parameters.retain(|(k, _)| k != key);
+        }
+    }
+
+    /// The unparsed value, for codecs like RED whose `a=fmtp` isn't in the
+    /// `;`-separated `key=value` form (e.g. `111/111`) and so isn't reachable
+    /// through [`parameters`](Self::parameters).
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+}
+
 impl ParsableAttribute for FormatParameters {
     fn parse<'a, E>(input: &'a str) -> nom::IResult<&'a str, Self, E>
     where
@@ -300,19 +493,32 @@ impl ParsableAttribute for FormatParameters {
         let (input, _) = char(':')(input)?;
         let (input, payload) = map_res(value_field, u8::from_str)(input)?;
         let (input, _) = field_separator(input)?;
-        let (input, parameters) = not_line_ending(input)?;
+        let (input, raw) = not_line_ending(input)?;
         let (input, _) = line_ending_or_eof(input)?;
 
         let fmtp = FormatParameters {
             payload,
-            parameters: parameters.to_owned(),
+            parameters: Self::parse_parameters(raw),
+            raw: raw.to_owned(),
         };
 
         Ok((input, fmtp))
     }
 
     fn to_string(&self) -> Option<String> {
-        Some(format!("{} {}", self.payload, self.parameters))
+        let parameters = match &self.parameters {
+            Some(parameters) => parameters
+                .iter()
+                .map(|(key, value)| match value {
+                    Some(value) => format!("{}={}", key, value),
+                    None => key.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(";"),
+            None => self.raw.clone(),
+        };
+
+        Some(format!("{} {}", self.payload, parameters))
     }
 }
 
@@ -440,6 +646,111 @@ impl ParsableAttribute for Candidate {
     }
 }
 
+/// RFC 8445 §5.1.2.1 recommended `type_preference` for the well-known
+/// [`IceCandidateType`] variants. Unknown/future variants get 0, same as
+/// relayed candidates, since we have no better default.
+fn recommended_type_preference(kind: &IceCandidateType) -> u32 {
+    match kind {
+        IceCandidateType::Host => 126,
+        IceCandidateType::PeerReflexive => 110,
+        IceCandidateType::ServerReflexive => 100,
+        IceCandidateType::Relayed => 0,
+        IceCandidateType::Unknown(_) => 0,
+    }
+}
+
+/// RFC 8445 §5.1.2.1 candidate priority formula.
+fn candidate_priority(type_preference: u32, local_preference: u16, component: u16) -> u32 {
+    2u32.pow(24) * type_preference + 2u32.pow(8) * local_preference as u32 + (256 - component as u32)
+}
+
+/// Builds a [`Candidate`], computing `priority` per the RFC 8445 §5.1.2.1
+/// formula instead of requiring the caller to get it right by hand.
+pub struct CandidateBuilder {
+    foundation: String,
+    component: u16,
+    transport: IceTransportType,
+    kind: IceCandidateType,
+    address: String,
+    port: u16,
+    local_preference: u16,
+    rel_addr: Option<String>,
+    rel_port: Option<u16>,
+    tcp_type: Option<IceTcpType>,
+}
+
+impl CandidateBuilder {
+    /// `component` is 1 for RTP and 2 for RTCP (RFC 5245 §4.1.1.1).
+    /// `local_preference` defaults to 65535, the recommended value when a
+    /// single address of one family is in use; call
+    /// [`local_preference`](Self::local_preference) to override it when
+    /// ordering multiple candidates of the same type.
+    pub fn new(
+        foundation: impl Into<String>,
+        transport: IceTransportType,
+        kind: IceCandidateType,
+        address: impl Into<String>,
+        port: u16,
+        component: u16,
+    ) -> Self {
+        CandidateBuilder {
+            foundation: foundation.into(),
+            component,
+            transport,
+            kind,
+            address: address.into(),
+            port,
+            local_preference: 65535,
+            rel_addr: None,
+            rel_port: None,
+            tcp_type: None,
+        }
+    }
+
+    pub fn local_preference(mut self, local_preference: u16) -> Self {
+        self.local_preference = local_preference;
+        self
+    }
+
+    pub fn rel_addr(mut self, rel_addr: impl Into<String>, rel_port: u16) -> Self {
+        self.rel_addr = Some(rel_addr.into());
+        self.rel_port = Some(rel_port);
+        self
+    }
+
+    pub fn tcp_type(mut self, tcp_type: IceTcpType) -> Self {
+        self.tcp_type = Some(tcp_type);
+        self
+    }
+
+    pub fn build(self) -> Candidate {
+        let priority = candidate_priority(recommended_type_preference(&self.kind), self.local_preference, self.component);
+
+        Candidate {
+            foundation: self.foundation,
+            component: self.component,
+            transport: self.transport,
+            priority,
+            address: self.address,
+            port: self.port,
+            kind: self.kind,
+            rel_addr: self.rel_addr,
+            rel_port: self.rel_port,
+            unknown: HashMap::new(),
+            tcp_type: self.tcp_type,
+        }
+    }
+}
+
+impl Candidate {
+    /// Recomputes `priority` per the RFC 8445 §5.1.2.1 formula for this
+    /// candidate's `kind` and `component`, using `local_preference` (see
+    /// [`CandidateBuilder::local_preference`]).
+    pub fn recompute_priority(&mut self, local_preference: u16) {
+        self.priority = candidate_priority(recommended_type_preference(&self.kind), local_preference, self.component);
+    }
+}
+
 // RFC 5245
 declare_property_sdp_attribute!("ice-lite", IceLite);
 
@@ -699,10 +1010,13 @@ impl ParsableAttribute for Rtcp {
     }
 }
 
-// RFC 4585
+/// `a=rtcp-fb:<pt> <id> [<param>]` (RFC 4585/5104/4588/8888): `id`/`param`
+/// cover `nack`/`nack pli`/`ccm fir`/`goog-remb`/`transport-cc` and anything
+/// else a peer advertises, since the set of feedback types isn't closed.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct RtcpFeedback {
-    // None is used for `*`
+    /// `None` for the `*` wildcard, applying to every payload type in the
+    /// media section.
     pub payload: Option<u8>,
     // TODO: Do we want to define these further?
     //       We probably do want some enums for all the various modes.
@@ -778,7 +1092,11 @@ impl ParsableAttribute for ExtensionMap {
             + FromExternalError<&'a str, std::num::ParseIntError>,
     {
         let (input, _) = char(':')(input)?;
-        let (input, id) = map_res(take_till1(|c| c == ' ' || c == '/'), u16::from_str)(input)?;
+        // 1-14 fit in the one-byte form, 15-255 need the two-byte form; 0 and
+        // anything above 255 are never valid (RFC 8285 §4.2).
+        let (input, id) = verify(map_res(take_till1(|c| c == ' ' || c == '/'), u16::from_str), |&id| {
+            (1..=255).contains(&id)
+        })(input)?;
         let (input, direction) = opt(preceded(
             char('/'),
             map_res(value_field, ExtensionMapDirection::from_str),
@@ -900,3 +1218,189 @@ impl ParsableAttribute for MediaStreamIdSemantic {
         Some(format!(" {} {}", self.semantic, self.msids.join(" ")))
     }
 }
+
+// RFC 8851
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Rid {
+    pub rid: crate::types::Rid,
+    pub direction: RidDirection,
+    pub formats: Vec<u8>,
+    // Restriction params other than `pt`, e.g. `max-width`/`max-height`. We don't
+    // interpret these any further, just keep them around for round-tripping.
+    pub restrictions: Vec<(String, String)>,
+}
+
+impl_value_sdp_attribute!("rid", Rid);
+
+impl ParsableAttribute for Rid {
+    fn parse<'a, E>(input: &'a str) -> nom::IResult<&'a str, Self, E>
+    where
+        E: ParseError<&'a str>
+            + ContextError<&'a str>
+            + FromExternalError<&'a str, crate::EnumParseError>
+            + FromExternalError<&'a str, std::num::ParseIntError>,
+    {
+        let (input, _) = char(':')(input)?;
+        // rid-id = 1*(ALPHA / DIGIT / "-" / "_")
+        let (input, rid) = verify(value_field, |rid: &str| {
+            !rid.is_empty() && rid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })(input)?;
+        let (input, _) = field_separator(input)?;
+        let (input, direction) = map_res(value_field, RidDirection::from_str)(input)?;
+        let (input, params) = opt(preceded(field_separator, not_line_ending))(input)?;
+        let (input, _) = line_ending_or_eof(input)?;
+
+        let mut formats = Vec::new();
+        let mut restrictions = Vec::new();
+
+        for param in params.into_iter().flat_map(|params| params.split(';')) {
+            let (key, value) = match param.split_once('=') {
+                Some(pair) => pair,
+                None => continue,
+            };
+
+            if key == "pt" {
+                for format in value.split(',') {
+                    let format = u8::from_str(format)
+                        .map_err(|e| nom::Err::Error(E::from_external_error(format, nom::error::ErrorKind::MapRes, e)))?;
+                    formats.push(format);
+                }
+            } else {
+                restrictions.push((key.to_owned(), value.to_owned()));
+            }
+        }
+
+        let rid = Rid {
+            rid: crate::types::Rid::from(rid),
+            direction,
+            formats,
+            restrictions,
+        };
+
+        Ok((input, rid))
+    }
+
+    fn to_string(&self) -> Option<String> {
+        let mut params = Vec::new();
+
+        if !self.formats.is_empty() {
+            let formats = self.formats.iter().map(u8::to_string).collect::<Vec<_>>().join(",");
+            params.push(format!("pt={}", formats));
+        }
+
+        params.extend(self.restrictions.iter().map(|(key, value)| format!("{}={}", key, value)));
+
+        let value = if params.is_empty() {
+            format!("{} {}", self.rid, self.direction)
+        } else {
+            format!("{} {} {}", self.rid, self.direction, params.join(";"))
+        };
+
+        Some(value)
+    }
+}
+
+// RFC 8853
+/// One `sc-id` in an `a=simulcast` alternative list: a rid reference,
+/// optionally `~`-prefixed to mark that stream as initially paused. Cross
+/// referencing this against the media section's `a=rid` lines happens in
+/// [`RtpMediaDescription::from_sdp`](crate::webrtc::RtpMediaDescription::from_sdp),
+/// not here, since that's the only layer with visibility into both attributes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SimulcastRid {
+    pub rid: crate::types::Rid,
+    pub paused: bool,
+}
+
+/// `a=simulcast:<sc-str-list> [SP <sc-str-list>]` (RFC 8853): each
+/// `sc-str-list` is a `send`/`recv` direction followed by its ordered list of
+/// alternatives, and each alternative is itself an ordered list of
+/// [`SimulcastRid`]s the receiver may fall back between.
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Simulcast {
+    pub send: Vec<Vec<SimulcastRid>>,
+    pub receive: Vec<Vec<SimulcastRid>>,
+}
+
+impl_value_sdp_attribute!("simulcast", Simulcast);
+
+fn parse_simulcast_alt_list(value: &str) -> Vec<Vec<SimulcastRid>> {
+    value
+        .split(';')
+        .map(|alternative| {
+            alternative
+                .split(',')
+                .map(|id| match id.strip_prefix('~') {
+                    Some(id) => SimulcastRid {
+                        rid: crate::types::Rid::from(id),
+                        paused: true,
+                    },
+                    None => SimulcastRid {
+                        rid: crate::types::Rid::from(id),
+                        paused: false,
+                    },
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn format_simulcast_alt_list(alternatives: &[Vec<SimulcastRid>]) -> String {
+    alternatives
+        .iter()
+        .map(|alternative| {
+            alternative
+                .iter()
+                .map(|stream| match stream.paused {
+                    true => format!("~{}", stream.rid),
+                    false => stream.rid.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+impl ParsableAttribute for Simulcast {
+    fn parse<'a, E>(input: &'a str) -> nom::IResult<&'a str, Self, E>
+    where
+        E: ParseError<&'a str>
+            + ContextError<&'a str>
+            + FromExternalError<&'a str, crate::EnumParseError>
+            + FromExternalError<&'a str, std::num::ParseIntError>,
+    {
+        let (input, _) = char(':')(input)?;
+        let (input, send) = opt(preceded(tag_no_case("send "), value_field))(input)?;
+        let (input, _) = opt(field_separator)(input)?;
+        let (input, receive) = opt(preceded(tag_no_case("recv "), value_field))(input)?;
+        let (input, _) = line_ending_or_eof(input)?;
+
+        let simulcast = Simulcast {
+            send: send.map(parse_simulcast_alt_list).unwrap_or_default(),
+            receive: receive.map(parse_simulcast_alt_list).unwrap_or_default(),
+        };
+
+        Ok((input, simulcast))
+    }
+
+    fn to_string(&self) -> Option<String> {
+        let mut clauses = Vec::new();
+
+        if !self.send.is_empty() {
+            clauses.push(format!("send {}", format_simulcast_alt_list(&self.send)));
+        }
+
+        if !self.receive.is_empty() {
+            clauses.push(format!("recv {}", format_simulcast_alt_list(&self.receive)));
+        }
+
+        Some(clauses.join(" "))
+    }
+}
+
+// RFC 8841
+declare_simple_value_sdp_attribute!("sctp-port", SctpPort, u16);
+
+// RFC 8841
+declare_simple_value_sdp_attribute!("max-message-size", MaxMessageSize, u64);
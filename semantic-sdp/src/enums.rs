@@ -2,6 +2,7 @@ use semantic_sdp_derive::SdpEnum;
 
 #[non_exhaustive]
 #[derive(Debug, Clone, SdpEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NetworkType {
     // RFC 4566
     #[sdp("IN")]
@@ -10,6 +11,7 @@ pub enum NetworkType {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, SdpEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AddressType {
     // RFC 4566
     #[sdp("IP4")]
@@ -20,6 +22,7 @@ pub enum AddressType {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, SdpEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BandwidthType {
     // RFC 3556 / 4566
     #[sdp("CT")]
@@ -37,6 +40,7 @@ pub enum BandwidthType {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, SdpEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MediaType {
     // RFC 4566
     #[sdp("audio")]
@@ -56,6 +60,7 @@ pub enum MediaType {
 
 #[non_exhaustive]
 #[derive(Debug, Clone, SdpEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TransportProtocol {
     // RFC 4566
     #[sdp("udp")]
@@ -191,6 +196,26 @@ pub enum FingerprintHashFunction {
     Unknown(String),
 }
 
+/// DTLS-SRTP crypto suites, named as `SSL_CTX_set_tlsext_use_srtp` and the
+/// `use_srtp` TLS extension (RFC 5764) expect them. Unlike most enums in
+/// this file these are never parsed out of SDP itself — DTLS-SRTP profile
+/// selection happens inside the TLS handshake, not `a=crypto` — but reusing
+/// [`SdpEnum`](semantic_sdp_derive::SdpEnum) still gets us the canonical
+/// name rendering the native library's `srtpProtectionProfiles` property
+/// expects.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, SdpEnum)]
+pub enum SrtpProtectionProfile {
+    #[sdp("SRTP_AES128_CM_SHA1_80")]
+    Aes128CmSha1_80,
+    #[sdp("SRTP_AES128_CM_SHA1_32")]
+    Aes128CmSha1_32,
+    #[sdp("SRTP_AEAD_AES_128_GCM")]
+    AeadAes128Gcm,
+    #[sdp("SRTP_AEAD_AES_256_GCM")]
+    AeadAes256Gcm,
+}
+
 #[non_exhaustive]
 #[derive(Debug, Clone, SdpEnum)]
 pub enum GroupSemantics {
@@ -216,6 +241,9 @@ pub enum SsrcGroupSemantics {
     FlowIdentification,
     #[sdp("FEC")]
     ForwardErrorCorrection,
+    // RFC 5956
+    #[sdp("FEC-FR")]
+    ForwardErrorCorrectionFlowReduced,
 
     #[sdp(default)]
     Unknown(String),
@@ -0,0 +1,590 @@
+//! Conversion between Jingle (XEP-0166/0167/0176/0320/0339) XML elements and
+//! [`UnifiedBundleSession`](crate::webrtc::UnifiedBundleSession), for
+//! XMPP/Jingle signaling (e.g. Prosody/Jitsi) as an alternative to raw SDP
+//! offer/answer.
+//!
+//! This is deliberately not a general-purpose XML library: it only understands the handful of elements/attributes Jingle
+//! uses for ICE-UDP transports, RTP descriptions, and SSRC sources, found
+//! with plain substring scanning rather than a real parser.
+
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::attributes::Candidate;
+use crate::enums::{
+    FingerprintHashFunction, IceCandidateType, IceTransportType, MediaType, RtpCodecName, SetupRole, TransportProtocol,
+};
+use crate::types::{CertificateFingerprint, Mid, PayloadType, Ssrc};
+use crate::webrtc::{MediaDescription, MediaDirection, RtpEncoding, RtpMediaDescription, RtpPayload, UnifiedBundleSession};
+
+fn attr<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let needle = format!("{}=\"", name);
+    let start = element.find(&needle)? + needle.len();
+    let end = element[start..].find('"')? + start;
+    Some(&element[start..end])
+}
+
+/// Scans `xml` for every `<tag ...>...</tag>` or `<tag .../>` element,
+/// returning each element's full text (including its tags). Matches are found
+/// at any nesting depth; the scan resumes after the end of each match.
+fn extract_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open_prefix = format!("<{}", tag);
+    let close_tag = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open_prefix) {
+        let after_open = &rest[start..];
+
+        // Don't let e.g. "<source" match inside "<source-extension".
+        match after_open[open_prefix.len()..].chars().next() {
+            Some(' ') | Some('>') | Some('/') => (),
+            _ => {
+                rest = &after_open[open_prefix.len()..];
+                continue;
+            }
+        }
+
+        let tag_end = match after_open.find('>') {
+            Some(index) => index + 1,
+            None => break,
+        };
+
+        if after_open.as_bytes()[tag_end - 2] == b'/' {
+            elements.push(&after_open[..tag_end]);
+            rest = &after_open[tag_end..];
+        } else if let Some(close) = after_open.find(&close_tag) {
+            elements.push(&after_open[..close + close_tag.len()]);
+            rest = &after_open[close + close_tag.len()..];
+        } else {
+            break;
+        }
+    }
+
+    elements
+}
+
+fn candidate_type(value: &str) -> IceCandidateType {
+    match value {
+        "srflx" => IceCandidateType::ServerReflexive,
+        "prflx" => IceCandidateType::PeerReflexive,
+        "relay" => IceCandidateType::Relayed,
+        _ => IceCandidateType::Host,
+    }
+}
+
+fn candidate_type_name(kind: &IceCandidateType) -> &'static str {
+    match kind {
+        IceCandidateType::ServerReflexive => "srflx",
+        IceCandidateType::PeerReflexive => "prflx",
+        IceCandidateType::Relayed => "relay",
+        IceCandidateType::Host | IceCandidateType::Unknown(_) => "host",
+    }
+}
+
+fn candidate_from_xml(element: &str) -> Option<Candidate> {
+    Some(Candidate {
+        foundation: attr(element, "foundation")?.to_owned(),
+        component: attr(element, "component")?.parse().ok()?,
+        transport: IceTransportType::Udp,
+        priority: attr(element, "priority")?.parse().ok()?,
+        address: attr(element, "ip")?.to_owned(),
+        port: attr(element, "port")?.parse().ok()?,
+        kind: candidate_type(attr(element, "type")?),
+        rel_addr: attr(element, "rel-addr").map(|s| s.to_owned()),
+        rel_port: attr(element, "rel-port").and_then(|s| s.parse().ok()),
+        unknown: Default::default(),
+        tcp_type: None,
+    })
+}
+
+fn candidate_to_xml(candidate: &Candidate, generation: u32) -> String {
+    let rel = match (&candidate.rel_addr, candidate.rel_port) {
+        (Some(rel_addr), Some(rel_port)) => format!(r#" rel-addr="{}" rel-port="{}""#, rel_addr, rel_port),
+        _ => String::new(),
+    };
+
+    format!(
+        r#"<candidate component="{}" foundation="{}" generation="{}" id="{}" ip="{}" network="0" port="{}" priority="{}" protocol="udp" type="{}"{}/>"#,
+        candidate.component,
+        candidate.foundation,
+        generation,
+        candidate.foundation,
+        candidate.address,
+        candidate.port,
+        candidate.priority,
+        candidate_type_name(&candidate.kind),
+        rel,
+    )
+}
+
+fn fingerprint_from_xml(element: &str) -> Option<(CertificateFingerprint, SetupRole)> {
+    let start = element.find('>')? + 1;
+    let end = element.find("</fingerprint>")?;
+
+    let hash_function = FingerprintHashFunction::from_str(attr(element, "hash")?).ok()?;
+    let fingerprint = CertificateFingerprint::from_hex_digest(hash_function, element[start..end].trim()).ok()?;
+    let setup = SetupRole::from_str(attr(element, "setup")?).ok()?;
+
+    Some((fingerprint, setup))
+}
+
+fn fingerprint_to_xml(fingerprint: &CertificateFingerprint, setup: &SetupRole) -> String {
+    format!(
+        r#"<fingerprint xmlns="urn:xmpp:jingle:apps:dtls:0" hash="{}" setup="{}">{}</fingerprint>"#,
+        fingerprint.hash_function,
+        setup,
+        fingerprint.digest_hex(),
+    )
+}
+
+fn payload_type_from_xml(element: &str) -> Option<RtpPayload> {
+    let parameters = extract_elements(element, "parameter")
+        .into_iter()
+        .filter_map(|parameter| Some((attr(parameter, "name")?.to_owned(), attr(parameter, "value")?.to_owned())))
+        .collect();
+
+    let supported_feedback = extract_elements(element, "rtcp-fb")
+        .into_iter()
+        .filter_map(|rtcp_fb| Some((attr(rtcp_fb, "type")?.to_owned(), attr(rtcp_fb, "subtype").map(|s| s.to_owned()))))
+        .collect();
+
+    let name = attr(element, "name")?;
+
+    Some(RtpPayload {
+        payload_type: PayloadType::from_str(attr(element, "id")?).ok()?,
+        name: RtpCodecName::from_str(name).unwrap_or_else(|_| RtpCodecName::Unknown(name.to_owned())),
+        clock: attr(element, "clockrate")?.parse().ok()?,
+        channels: attr(element, "channels").and_then(|value| value.parse().ok()),
+        parameters,
+        supported_feedback,
+        rtx_payload_type: None,
+    })
+}
+
+fn payload_type_to_xml(payload_type: PayloadType, name: &RtpCodecName, clock: u32, channels: Option<u8>, parameters: &[(String, String)], supported_feedback: &[(String, Option<String>)]) -> String {
+    let channels = match channels {
+        Some(channels) => format!(r#" channels="{}""#, channels),
+        None => String::new(),
+    };
+
+    let parameters = parameters
+        .iter()
+        .map(|(key, value)| format!(r#"<parameter name="{}" value="{}"/>"#, key, value))
+        .collect::<Vec<_>>()
+        .join("");
+
+    let feedback = supported_feedback
+        .iter()
+        .map(|(id, param)| {
+            let subtype = match param {
+                Some(param) => format!(r#" subtype="{}""#, param),
+                None => String::new(),
+            };
+            format!(r#"<rtcp-fb xmlns="urn:xmpp:jingle:apps:rtp:rtcp-fb:0" type="{}"{}/>"#, id, subtype)
+        })
+        .collect::<Vec<_>>()
+        .join("");
+
+    format!(
+        r#"<payload-type id="{}" name="{}" clockrate="{}"{}>{}{}</payload-type>"#,
+        payload_type, name, clock, channels, parameters, feedback,
+    )
+}
+
+/// One Jingle `<content/>` element (XEP-0166 §7.1): a media description
+/// paired with its ICE-UDP transport. `encodings` only ever carries
+/// [`RtpEncoding::SendingSsrc`](crate::webrtc::RtpEncoding::SendingSsrc)
+/// entries round-tripped through `<source/>`/`<ssrc-group/>` (XEP-0339);
+/// RID-based simulcast has no standard Jingle representation and is dropped.
+#[derive(Debug, Clone)]
+pub struct JingleContent {
+    pub name: String,
+    pub senders: MediaDirection,
+    pub media: MediaType,
+    pub payloads: Vec<RtpPayload>,
+    pub encodings: Vec<RtpEncoding>,
+    pub ufrag: String,
+    pub pwd: String,
+    pub candidates: Vec<Candidate>,
+    pub fingerprint: Option<(CertificateFingerprint, SetupRole)>,
+}
+
+fn senders_to_direction(value: &str) -> Option<MediaDirection> {
+    match value {
+        "both" => Some(MediaDirection::SendReceive),
+        "initiator" => Some(MediaDirection::ReceiveOnly),
+        "responder" => Some(MediaDirection::SendOnly),
+        "none" => Some(MediaDirection::Inactive),
+        _ => None,
+    }
+}
+
+fn direction_to_senders(direction: &MediaDirection) -> &'static str {
+    match direction {
+        MediaDirection::SendReceive => "both",
+        MediaDirection::ReceiveOnly => "initiator",
+        MediaDirection::SendOnly => "responder",
+        MediaDirection::Inactive => "none",
+    }
+}
+
+impl JingleContent {
+    pub fn from_xml(element: &str) -> Result<JingleContent, String> {
+        let name = attr(element, "name").ok_or("content is missing a name")?.to_owned();
+
+        let senders = attr(element, "senders")
+            .and_then(senders_to_direction)
+            .unwrap_or(MediaDirection::SendReceive);
+
+        let description = extract_elements(element, "description")
+            .into_iter()
+            .next()
+            .ok_or("content is missing a description")?;
+
+        let media = attr(description, "media").unwrap_or("");
+        let media = MediaType::from_str(media).unwrap_or_else(|_| MediaType::Unknown(media.to_owned()));
+
+        let mut payloads: Vec<_> = extract_elements(description, "payload-type")
+            .into_iter()
+            .filter_map(payload_type_from_xml)
+            .collect();
+
+        // Fold standalone "rtx" payload-types (keyed by their "apt" parameter)
+        // back into the primary payload they repair, mirroring
+        // RtpMediaDescription::from_sdp's a=fmtp:apt handling.
+        let rtx_payload_types: Vec<_> = payloads
+            .iter()
+            .filter(|payload| matches!(payload.name, RtpCodecName::Rtx))
+            .filter_map(|payload| {
+                let apt = payload.parameters.get("apt")?;
+                Some((PayloadType::from_str(apt).ok()?, payload.payload_type))
+            })
+            .collect();
+
+        payloads.retain(|payload| !matches!(payload.name, RtpCodecName::Rtx));
+
+        for payload in &mut payloads {
+            payload.rtx_payload_type = rtx_payload_types
+                .iter()
+                .find(|(apt, _)| *apt == payload.payload_type)
+                .map(|(_, rtx)| *rtx);
+        }
+
+        let ssrc_cnames: std::collections::HashMap<Ssrc, String> = extract_elements(description, "source")
+            .into_iter()
+            .filter_map(|source| {
+                let ssrc = Ssrc::from_str(attr(source, "ssrc")?).ok()?;
+                let cname = extract_elements(source, "parameter")
+                    .into_iter()
+                    .find(|parameter| attr(parameter, "name") == Some("cname"))
+                    .and_then(|parameter| attr(parameter, "value"))?;
+                Some((ssrc, cname.to_owned()))
+            })
+            .collect();
+
+        let fid_group = extract_elements(description, "ssrc-group")
+            .into_iter()
+            .find(|group| attr(group, "semantics") == Some("FID"));
+
+        let encodings = match fid_group {
+            Some(group) => {
+                let ssrcs: Vec<Ssrc> = extract_elements(group, "source")
+                    .into_iter()
+                    .filter_map(|source| Ssrc::from_str(attr(source, "ssrc")?).ok())
+                    .collect();
+
+                match (ssrcs.first(), ssrcs.get(1)) {
+                    (Some(&ssrc), rtx_ssrc) => vec![RtpEncoding::SendingSsrc {
+                        cname: ssrc_cnames.get(&ssrc).cloned().unwrap_or_default(),
+                        ssrc,
+                        rtx_ssrc: rtx_ssrc.copied(),
+                        fec_ssrc: None,
+                    }],
+                    _ => Vec::new(),
+                }
+            }
+            None => ssrc_cnames
+                .iter()
+                .map(|(&ssrc, cname)| RtpEncoding::SendingSsrc {
+                    cname: cname.clone(),
+                    ssrc,
+                    rtx_ssrc: None,
+                    fec_ssrc: None,
+                })
+                .collect(),
+        };
+
+        let transport = extract_elements(element, "transport")
+            .into_iter()
+            .next()
+            .ok_or("content is missing a transport")?;
+
+        let ufrag = attr(transport, "ufrag").ok_or("transport is missing ufrag")?.to_owned();
+        let pwd = attr(transport, "pwd").ok_or("transport is missing pwd")?.to_owned();
+
+        let candidates = extract_elements(transport, "candidate")
+            .into_iter()
+            .filter_map(candidate_from_xml)
+            .collect();
+
+        let fingerprint = extract_elements(transport, "fingerprint").into_iter().next().and_then(fingerprint_from_xml);
+
+        Ok(JingleContent {
+            name,
+            senders,
+            media,
+            payloads,
+            encodings,
+            ufrag,
+            pwd,
+            candidates,
+            fingerprint,
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut payload_types = String::new();
+
+        for payload in &self.payloads {
+            let parameters: Vec<_> = payload.parameters.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            let supported_feedback: Vec<_> = payload.supported_feedback.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+
+            payload_types.push_str(&payload_type_to_xml(
+                payload.payload_type,
+                &payload.name,
+                payload.clock,
+                payload.channels,
+                &parameters,
+                &supported_feedback,
+            ));
+
+            if let Some(rtx_payload_type) = payload.rtx_payload_type {
+                payload_types.push_str(&payload_type_to_xml(
+                    rtx_payload_type,
+                    &RtpCodecName::Rtx,
+                    payload.clock,
+                    payload.channels,
+                    &[("apt".to_owned(), payload.payload_type.to_string())],
+                    &[],
+                ));
+            }
+        }
+
+        let mut sources = String::new();
+        let mut ssrc_groups = String::new();
+
+        for encoding in &self.encodings {
+            // RID-based simulcast has no standard Jingle representation.
+            let RtpEncoding::SendingSsrc { cname, ssrc, rtx_ssrc, .. } = encoding else {
+                continue;
+            };
+
+            sources.push_str(&format!(
+                r#"<source xmlns="urn:xmpp:jingle:apps:rtp:ssma:0" ssrc="{}"><parameter name="cname" value="{}"/></source>"#,
+                ssrc, cname,
+            ));
+
+            let rtx_ssrc = match rtx_ssrc {
+                Some(rtx_ssrc) => rtx_ssrc,
+                None => continue,
+            };
+
+            sources.push_str(&format!(
+                r#"<source xmlns="urn:xmpp:jingle:apps:rtp:ssma:0" ssrc="{}"><parameter name="cname" value="{}"/></source>"#,
+                rtx_ssrc, cname,
+            ));
+
+            ssrc_groups.push_str(&format!(
+                r#"<ssrc-group xmlns="urn:xmpp:jingle:apps:rtp:ssma:0" semantics="FID"><source ssrc="{}"/><source ssrc="{}"/></ssrc-group>"#,
+                ssrc, rtx_ssrc,
+            ));
+        }
+
+        let description = format!(
+            r#"<description xmlns="urn:xmpp:jingle:apps:rtp:1" media="{}">{}{}{}</description>"#,
+            self.media, payload_types, sources, ssrc_groups,
+        );
+
+        let candidates = self
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(generation, candidate)| candidate_to_xml(candidate, generation as u32))
+            .collect::<Vec<_>>()
+            .join("");
+
+        let fingerprint = self
+            .fingerprint
+            .as_ref()
+            .map(|(fingerprint, setup)| fingerprint_to_xml(fingerprint, setup))
+            .unwrap_or_default();
+
+        let transport = format!(
+            r#"<transport xmlns="urn:xmpp:jingle:transports:ice-udp:1" ufrag="{}" pwd="{}">{}{}</transport>"#,
+            self.ufrag, self.pwd, candidates, fingerprint,
+        );
+
+        format!(
+            r#"<content name="{}" creator="responder" senders="{}">{}{}</content>"#,
+            self.name,
+            direction_to_senders(&self.senders),
+            description,
+            transport,
+        )
+    }
+}
+
+/// The full set of Jingle `<content/>` elements for a session, plus the
+/// optional `urn:xmpp:jingle:apps:grouping:0` bundle `<group/>`. This is
+/// what a `session-initiate`/`session-accept` IQ's `<jingle/>` payload
+/// boils down to, as far as [`UnifiedBundleSession`] is concerned.
+#[derive(Debug, Clone, Default)]
+pub struct JingleContentGroup {
+    pub contents: Vec<JingleContent>,
+    pub bundle: Vec<String>,
+}
+
+impl JingleContentGroup {
+    pub fn from_xml(xml: &str) -> Result<JingleContentGroup, String> {
+        let groups = extract_elements(xml, "group");
+
+        // A bundle `<group/>` holds `<content name="..."/>` references that
+        // `extract_elements` would also match, so cut the groups out first.
+        let mut outside_groups = xml.to_owned();
+        for group in &groups {
+            outside_groups = outside_groups.replacen(group, "", 1);
+        }
+
+        let contents = extract_elements(&outside_groups, "content")
+            .into_iter()
+            .map(JingleContent::from_xml)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let bundle = groups
+            .into_iter()
+            .find(|group| attr(group, "semantics") == Some("BUNDLE"))
+            .map(|group| {
+                extract_elements(group, "content")
+                    .into_iter()
+                    .filter_map(|content| attr(content, "name").map(|name| name.to_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(JingleContentGroup { contents, bundle })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let contents = self.contents.iter().map(JingleContent::to_xml).collect::<Vec<_>>().join("");
+
+        let group = if self.bundle.is_empty() {
+            String::new()
+        } else {
+            let contents = self
+                .bundle
+                .iter()
+                .map(|name| format!(r#"<content name="{}"/>"#, name))
+                .collect::<Vec<_>>()
+                .join("");
+
+            format!(
+                r#"<group xmlns="urn:xmpp:jingle:apps:grouping:0" semantics="BUNDLE">{}</group>"#,
+                contents,
+            )
+        };
+
+        format!("{}{}", contents, group)
+    }
+}
+
+impl UnifiedBundleSession {
+    /// Imports a Jingle `session-initiate`/`session-accept` payload (already
+    /// split into `<content/>` elements by the XMPP layer) as a
+    /// [`UnifiedBundleSession`]. ICE ufrag/pwd/fingerprint/setup are taken
+    /// from the first content, same as [`Self::from_sdp`] takes them from the
+    /// first `m=` line.
+    pub fn from_jingle(group: &JingleContentGroup) -> Result<Self, String> {
+        let first_content = group.contents.first().ok_or("at least one content is required")?;
+
+        let ice_ufrag = first_content.ufrag.clone();
+        let ice_pwd = first_content.pwd.clone();
+        let candidates = first_content.candidates.clone();
+
+        let (fingerprints, setup_role) = match &first_content.fingerprint {
+            Some((fingerprint, setup)) => (vec![fingerprint.clone()], setup.clone()),
+            None => (Vec::new(), SetupRole::ActivePassive),
+        };
+
+        let media_descriptions = group
+            .contents
+            .iter()
+            .map(|content| {
+                MediaDescription::Rtp(RtpMediaDescription {
+                    kind: content.media.clone(),
+                    port: 9,
+                    protocol: TransportProtocol::UdpTlsRtpSavpf,
+                    bandwidths: Default::default(),
+                    mid: Mid::from(content.name.as_str()),
+                    payloads: content.payloads.clone(),
+                    direction: content.senders.clone(),
+                    encodings: content.encodings.clone(),
+                    redundancy: Vec::new(),
+                    extensions: Vec::new(),
+                    rtcp_mux: true,
+                    rtcp_mux_only: false,
+                    rtcp_reduced_size: false,
+                    simulcast: None,
+                })
+            })
+            .collect();
+
+        let mut rng = rand::thread_rng();
+
+        Ok(UnifiedBundleSession {
+            id: rng.gen_range(0, 9_223_372_036_854_775_807),
+            version: 1,
+            ice_lite: false,
+            ice_ufrag,
+            ice_pwd,
+            ice_options: Default::default(),
+            candidates,
+            fingerprints,
+            setup_role,
+            allow_mixed_extension_maps: false,
+            media_descriptions,
+        })
+    }
+
+    /// The inverse of [`Self::from_jingle`]: builds the Jingle `<content/>`
+    /// elements (plus bundle `<group/>`) for this session. SCTP/data-channel
+    /// media descriptions have no Jingle representation and are skipped.
+    pub fn to_jingle(&self) -> JingleContentGroup {
+        let fingerprint = self
+            .fingerprints
+            .first()
+            .map(|fingerprint| (fingerprint.clone(), self.setup_role.clone()));
+
+        let contents: Vec<_> = self
+            .media_descriptions
+            .iter()
+            .filter_map(MediaDescription::as_rtp)
+            .map(|media_description| JingleContent {
+                name: media_description.mid.0.clone(),
+                senders: media_description.direction.clone(),
+                media: media_description.kind.clone(),
+                payloads: media_description.payloads.clone(),
+                encodings: media_description.encodings.clone(),
+                ufrag: self.ice_ufrag.clone(),
+                pwd: self.ice_pwd.clone(),
+                candidates: self.candidates.clone(),
+                fingerprint: fingerprint.clone(),
+            })
+            .collect();
+
+        let bundle = contents.iter().map(|content| content.name.clone()).collect();
+
+        JingleContentGroup { contents, bundle }
+    }
+}
@@ -9,7 +9,10 @@ pub use attribute_map::AttributeMap;
 mod attribute_map;
 pub mod attributes;
 pub mod enums;
+pub mod jingle;
 pub mod sdp;
+pub mod types;
+pub mod webrtc;
 
 pub enum EnumParseError {
     VariantNotFound,
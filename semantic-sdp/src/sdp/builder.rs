@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::attributes::NamedAttribute;
+use crate::enums::{BandwidthType, MediaType, TransportProtocol};
+use crate::AttributeMap;
+
+use super::{Connection, EncryptionKey, MediaDescription, Origin, Session, Time};
+
+/// Builds a [`Session`] field by field, validating the handful of lines RFC
+/// 4566 requires (`o=`, at least one `t=`) at [`build`](Self::build) time
+/// instead of leaving callers to assemble the struct literal by hand.
+#[derive(Debug, Default)]
+pub struct SessionBuilder {
+    origin: Option<Origin>,
+    name: Option<String>,
+    information: Option<String>,
+    uri: Option<String>,
+    email_address: Option<String>,
+    phone_number: Option<String>,
+    connections: Vec<Connection>,
+    bandwidths: HashMap<BandwidthType, u64>,
+    times: Vec<Time>,
+    encryption_key: Option<EncryptionKey>,
+    attributes: AttributeMap,
+    media_descriptions: Vec<MediaDescription>,
+}
+
+impl SessionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn origin(mut self, origin: Origin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn information(mut self, information: impl Into<String>) -> Self {
+        self.information = Some(information.into());
+        self
+    }
+
+    pub fn uri(mut self, uri: impl Into<String>) -> Self {
+        self.uri = Some(uri.into());
+        self
+    }
+
+    pub fn email_address(mut self, email_address: impl Into<String>) -> Self {
+        self.email_address = Some(email_address.into());
+        self
+    }
+
+    pub fn phone_number(mut self, phone_number: impl Into<String>) -> Self {
+        self.phone_number = Some(phone_number.into());
+        self
+    }
+
+    pub fn connection(mut self, connection: Connection) -> Self {
+        self.connections.push(connection);
+        self
+    }
+
+    pub fn add_bandwidth(mut self, kind: BandwidthType, value: u64) -> Self {
+        self.bandwidths.insert(kind, value);
+        self
+    }
+
+    pub fn add_time(mut self, time: Time) -> Self {
+        self.times.push(time);
+        self
+    }
+
+    pub fn encryption_key(mut self, encryption_key: EncryptionKey) -> Self {
+        self.encryption_key = Some(encryption_key);
+        self
+    }
+
+    pub fn add_attribute<T: NamedAttribute>(mut self, attribute: T) -> Self {
+        self.attributes.append(attribute);
+        self
+    }
+
+    pub fn add_media(mut self, media_description: MediaDescription) -> Self {
+        self.media_descriptions.push(media_description);
+        self
+    }
+
+    pub fn build(self) -> Result<Session, String> {
+        let origin = self.origin.ok_or("session is missing an origin (o=) line")?;
+
+        if self.times.is_empty() {
+            return Err("session requires at least one time (t=) line".to_owned());
+        }
+
+        Ok(Session {
+            origin,
+            name: self.name,
+            information: self.information,
+            uri: self.uri,
+            email_address: self.email_address,
+            phone_number: self.phone_number,
+            connections: self.connections,
+            bandwidths: self.bandwidths,
+            times: self.times,
+            encryption_key: self.encryption_key,
+            attributes: self.attributes,
+            media_descriptions: self.media_descriptions,
+        })
+    }
+}
+
+/// Builds a [`MediaDescription`] field by field, validating the parts of the
+/// `m=` line RFC 4566 requires (`<media>`, `<port>`, `<proto>`, at least one
+/// `<fmt>`) at [`build`](Self::build) time.
+#[derive(Debug, Default)]
+pub struct MediaDescriptionBuilder {
+    kind: Option<MediaType>,
+    port: Option<u16>,
+    num_ports: Option<u16>,
+    protocol: Option<TransportProtocol>,
+    formats: Vec<String>,
+    title: Option<String>,
+    connections: Vec<Connection>,
+    bandwidths: HashMap<BandwidthType, u64>,
+    encryption_key: Option<EncryptionKey>,
+    attributes: AttributeMap,
+}
+
+impl MediaDescriptionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn kind(mut self, kind: MediaType) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    pub fn num_ports(mut self, num_ports: u16) -> Self {
+        self.num_ports = Some(num_ports);
+        self
+    }
+
+    pub fn protocol(mut self, protocol: TransportProtocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    pub fn add_format(mut self, format: impl Into<String>) -> Self {
+        self.formats.push(format.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn connection(mut self, connection: Connection) -> Self {
+        self.connections.push(connection);
+        self
+    }
+
+    pub fn add_bandwidth(mut self, kind: BandwidthType, value: u64) -> Self {
+        self.bandwidths.insert(kind, value);
+        self
+    }
+
+    pub fn encryption_key(mut self, encryption_key: EncryptionKey) -> Self {
+        self.encryption_key = Some(encryption_key);
+        self
+    }
+
+    pub fn add_attribute<T: NamedAttribute>(mut self, attribute: T) -> Self {
+        self.attributes.append(attribute);
+        self
+    }
+
+    pub fn build(self) -> Result<MediaDescription, String> {
+        let kind = self.kind.ok_or("media description is missing a kind (the m= line's <media>)")?;
+        let port = self.port.ok_or("media description is missing a port (the m= line's <port>)")?;
+        let protocol = self
+            .protocol
+            .ok_or("media description is missing a protocol (the m= line's <proto>)")?;
+
+        if self.formats.is_empty() {
+            return Err("media description requires at least one format (the m= line's <fmt>)".to_owned());
+        }
+
+        Ok(MediaDescription {
+            kind,
+            port,
+            num_ports: self.num_ports,
+            protocol,
+            formats: self.formats,
+            title: self.title,
+            connections: self.connections,
+            bandwidths: self.bandwidths,
+            encryption_key: self.encryption_key,
+            attributes: self.attributes,
+        })
+    }
+}
@@ -10,13 +10,18 @@ use nom::sequence::{preceded, separated_pair, terminated, tuple};
 
 use crate::attributes::{parse_attribute, ParsableAttribute};
 use crate::enums::*;
+use crate::types::SdpAddress;
 use crate::AttributeMap;
 use crate::{field_separator, field_separator_str, line_ending_or_eof, value_field};
 use std::collections::HashMap;
 
+mod builder;
 mod tests;
 
+pub use builder::{MediaDescriptionBuilder, SessionBuilder};
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Session {
     pub origin: Origin,
     pub name: Option<String>,
@@ -24,22 +29,31 @@ pub struct Session {
     pub uri: Option<String>,
     pub email_address: Option<String>,
     pub phone_number: Option<String>,
-    pub connection: Option<Connection>,
+    pub connections: Vec<Connection>,
     pub bandwidths: HashMap<BandwidthType, u64>,
     pub times: Vec<Time>,
-    pub encryption_key: Option<String>,
+    pub encryption_key: Option<EncryptionKey>,
     pub attributes: AttributeMap,
     pub media_descriptions: Vec<MediaDescription>,
 }
 
 impl Session {
+    /// The session-level `c=` line, for the common case of a single unicast
+    /// connection. Sessions with multiple multicast groups carry more than
+    /// one `Connection` in `connections`.
+    pub fn connection(&self) -> Option<&Connection> {
+        self.connections.first()
+    }
+
     fn parse<'a, E>(input: &'a str) -> Result<Self, nom::Err<E>>
     where
         E: ParseError<&'a str>
             + ContextError<&'a str>
             + FromExternalError<&'a str, crate::EnumParseError>
             + FromExternalError<&'a str, std::convert::Infallible>
-            + FromExternalError<&'a str, std::num::ParseIntError>,
+            + FromExternalError<&'a str, std::num::ParseIntError>
+            + FromExternalError<&'a str, std::net::AddrParseError>
+            + FromExternalError<&'a str, EncryptionKeyParseError>,
     {
         let (input, _) = char('v')(input)?;
         let (input, _) = char('=')(input)?;
@@ -52,10 +66,10 @@ impl Session {
         let (input, uri) = opt(parse_generic_line('u'))(input)?;
         let (input, email_address) = opt(parse_generic_line('e'))(input)?;
         let (input, phone_number) = opt(parse_generic_line('p'))(input)?;
-        let (input, connection) = opt(parse_connection_line)(input)?;
+        let (input, connections) = many0(parse_connection_line)(input)?;
         let (input, bandwidths) = many0(parse_bandwidth_line)(input)?;
         let (input, times) = many1(parse_time_lines)(input)?;
-        let (input, encryption_key) = opt(parse_generic_line('k'))(input)?;
+        let (input, encryption_key) = opt(parse_encryption_key_line)(input)?;
         let (input, parsed_attributes) = many0(parse_attribute_line)(input)?;
         let (input, media_descriptions) = many0(parse_media_description_lines)(input)?;
         eof(input)?;
@@ -72,10 +86,10 @@ impl Session {
             uri: uri.map(|s| s.to_owned()),
             email_address: email_address.map(|s| s.to_owned()),
             phone_number: phone_number.map(|s| s.to_owned()),
-            connection,
+            connections,
             bandwidths: bandwidths.into_iter().collect(),
             times,
-            encryption_key: encryption_key.map(|s| s.to_owned()),
+            encryption_key,
             attributes,
             media_descriptions,
         };
@@ -120,7 +134,7 @@ impl std::fmt::Display for Session {
             write!(f, "p={}\r\n", phone_number)?;
         }
 
-        if let Some(connection) = &self.connection {
+        for connection in &self.connections {
             write!(f, "{}", connection)?;
         }
 
@@ -147,13 +161,14 @@ impl std::fmt::Display for Session {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Origin {
     pub username: Option<String>,
     pub session_id: u64,
     pub session_version: u64,
     pub network_type: NetworkType,
     pub address_type: AddressType,
-    pub unicast_address: String,
+    pub unicast_address: SdpAddress,
 }
 
 impl std::fmt::Display for Origin {
@@ -175,28 +190,38 @@ impl std::fmt::Display for Origin {
     }
 }
 
-// TODO: We don't currently parse the extra fields required for multicast addresses.
-//       From an API PoV, the multiple c-line stuff would cause friction for unicast usage.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Connection {
     pub network_type: NetworkType,
     pub address_type: AddressType,
-    pub connection_address: String,
-    // pub multicast_ttl: Option<u8>,
-    // pub multicast_count: Option<u32>,
+    pub connection_address: SdpAddress,
+    /// RFC 4566 multicast TTL, e.g. the `127` in `224.2.1.1/127`. Only ever
+    /// set for `IP4` addresses; `IP6` multicast has no TTL field.
+    pub multicast_ttl: Option<u8>,
+    /// RFC 4566 multicast address count, e.g. the `3` in `224.2.1.1/127/3`
+    /// or `FF15::101/3`.
+    pub multicast_count: Option<u32>,
 }
 
 impl std::fmt::Display for Connection {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "c={} {} {}\r\n",
-            self.network_type, self.address_type, self.connection_address,
-        )
+        write!(f, "c={} {} {}", self.network_type, self.address_type, self.connection_address)?;
+
+        if let Some(multicast_ttl) = self.multicast_ttl {
+            write!(f, "/{}", multicast_ttl)?;
+        }
+
+        if let Some(multicast_count) = self.multicast_count {
+            write!(f, "/{}", multicast_count)?;
+        }
+
+        write!(f, "\r\n")
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Time {
     pub start: u64,
     pub stop: u64,
@@ -229,6 +254,7 @@ impl std::fmt::Display for Time {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RepeatTime {
     pub repeat_interval: u64,
     pub active_duration: u64,
@@ -248,6 +274,7 @@ impl std::fmt::Display for RepeatTime {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimeZoneAdjustment {
     pub adjustment_time: u64,
     pub offset: i64,
@@ -259,7 +286,85 @@ impl std::fmt::Display for TimeZoneAdjustment {
     }
 }
 
+/// Seconds between the NTP epoch (1900-01-01) used by `t=`/`r=`/`z=` lines
+/// and the Unix epoch (1970-01-01) that [`chrono::DateTime<Utc>`] is built on.
+#[cfg(feature = "chrono")]
+const NTP_TO_UNIX_EPOCH_OFFSET: u64 = 2_208_988_800;
+
+#[cfg(feature = "chrono")]
+impl Time {
+    /// The wall-clock instant `self.start` refers to, or `None` if it's the
+    /// special value `0` meaning the session is permanent/unbounded (RFC 4566 §5.9).
+    pub fn start_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        ntp_timestamp_to_datetime(self.start)
+    }
+
+    /// The wall-clock instant `self.stop` refers to, or `None` if it's the
+    /// special value `0` meaning the session doesn't have a fixed end time.
+    pub fn stop_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        ntp_timestamp_to_datetime(self.stop)
+    }
+
+    /// Builds a `Time` spanning `[start, stop)`, with `stop` of `None` taken
+    /// to mean "unbounded" (encoded as the special value `0`).
+    pub fn from_datetimes(
+        start: chrono::DateTime<chrono::Utc>,
+        stop: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Self {
+        Self {
+            start: datetime_to_ntp_timestamp(start),
+            stop: stop.map(datetime_to_ntp_timestamp).unwrap_or(0),
+            repeat_times: Vec::new(),
+            time_zone_adjustments: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl RepeatTime {
+    pub fn repeat_interval(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.repeat_interval as i64)
+    }
+
+    pub fn active_duration(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.active_duration as i64)
+    }
+
+    pub fn offsets(&self) -> impl Iterator<Item = chrono::Duration> + '_ {
+        self.offsets.iter().map(|&offset| chrono::Duration::seconds(offset as i64))
+    }
+
+    /// Builds a `RepeatTime` from wall-clock durations.
+    pub fn from_durations(
+        repeat_interval: chrono::Duration,
+        active_duration: chrono::Duration,
+        offsets: impl IntoIterator<Item = chrono::Duration>,
+    ) -> Self {
+        Self {
+            repeat_interval: repeat_interval.num_seconds() as u64,
+            active_duration: active_duration.num_seconds() as u64,
+            offsets: offsets.into_iter().map(|offset| offset.num_seconds() as u64).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn ntp_timestamp_to_datetime(timestamp: u64) -> Option<chrono::DateTime<chrono::Utc>> {
+    if timestamp == 0 {
+        return None;
+    }
+
+    let unix_timestamp = timestamp.saturating_sub(NTP_TO_UNIX_EPOCH_OFFSET);
+    chrono::DateTime::from_timestamp(unix_timestamp as i64, 0)
+}
+
+#[cfg(feature = "chrono")]
+fn datetime_to_ntp_timestamp(datetime: chrono::DateTime<chrono::Utc>) -> u64 {
+    (datetime.timestamp() as u64).saturating_add(NTP_TO_UNIX_EPOCH_OFFSET)
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MediaDescription {
     pub kind: MediaType,
     pub port: u16,
@@ -268,14 +373,20 @@ pub struct MediaDescription {
     pub formats: Vec<String>,
 
     pub title: Option<String>,
-    // TODO: A media section can have multiple connection lines with multicast addresses,
-    //       We're just not supporting multicast currently.
-    pub connection: Option<Connection>,
+    pub connections: Vec<Connection>,
     pub bandwidths: HashMap<BandwidthType, u64>,
-    pub encryption_key: Option<String>,
+    pub encryption_key: Option<EncryptionKey>,
     pub attributes: AttributeMap,
 }
 
+impl MediaDescription {
+    /// The media-level `c=` line, for the common case of a single unicast
+    /// connection. See [`Session::connection`].
+    pub fn connection(&self) -> Option<&Connection> {
+        self.connections.first()
+    }
+}
+
 impl std::fmt::Display for MediaDescription {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let num_ports = if let Some(num_ports) = self.num_ports {
@@ -298,7 +409,7 @@ impl std::fmt::Display for MediaDescription {
             write!(f, "i={}\r\n", title)?;
         }
 
-        if let Some(connection) = &self.connection {
+        for connection in &self.connections {
             write!(f, "{}", connection)?;
         }
 
@@ -316,12 +427,65 @@ impl std::fmt::Display for MediaDescription {
     }
 }
 
+/// A `k=` line: `<method>` or `<method>:<encryption key>`, RFC 4566 §5.12.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EncryptionKey {
+    Clear(String),
+    Base64(Vec<u8>),
+    Uri(String),
+    Prompt,
+}
+
+impl EncryptionKey {
+    fn parse(value: &str) -> Result<Self, EncryptionKeyParseError> {
+        let (method, rest) = match value.split_once(':') {
+            Some((method, rest)) => (method, Some(rest)),
+            None => (value, None),
+        };
+
+        match method {
+            "clear" => Ok(Self::Clear(rest.ok_or(EncryptionKeyParseError::MissingValue)?.to_owned())),
+            "base64" => {
+                let value = rest.ok_or(EncryptionKeyParseError::MissingValue)?;
+                Ok(Self::Base64(base64_decode(value).ok_or(EncryptionKeyParseError::InvalidBase64)?))
+            }
+            "uri" => Ok(Self::Uri(rest.ok_or(EncryptionKeyParseError::MissingValue)?.to_owned())),
+            "prompt" => match rest {
+                None => Ok(Self::Prompt),
+                Some(_) => Err(EncryptionKeyParseError::UnexpectedValue),
+            },
+            _ => Err(EncryptionKeyParseError::UnknownMethod),
+        }
+    }
+}
+
+impl std::fmt::Display for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Clear(value) => write!(f, "clear:{}", value),
+            Self::Base64(bytes) => write!(f, "base64:{}", base64_encode(bytes)),
+            Self::Uri(value) => write!(f, "uri:{}", value),
+            Self::Prompt => write!(f, "prompt"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EncryptionKeyParseError {
+    UnknownMethod,
+    MissingValue,
+    UnexpectedValue,
+    InvalidBase64,
+}
+
 fn parse_origin_line<'a, E>(input: &'a str) -> nom::IResult<&'a str, Origin, E>
 where
     E: ParseError<&'a str>
         + FromExternalError<&'a str, crate::EnumParseError>
         + FromExternalError<&'a str, std::convert::Infallible>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, std::net::AddrParseError>,
 {
     let (input, _) = char('o')(input)?;
     let (input, _) = char('=')(input)?;
@@ -335,7 +499,7 @@ where
     let (input, _) = field_separator(input)?;
     let (input, address_type) = map_res(value_field, AddressType::from_str)(input)?;
     let (input, _) = field_separator(input)?;
-    let (input, unicast_address) = value_field(input)?;
+    let (input, unicast_address) = map_res(value_field, |v| SdpAddress::parse(&address_type, v))(input)?;
     let (input, _) = line_ending_or_eof(input)?;
 
     let origin = Origin {
@@ -344,7 +508,7 @@ where
         session_version,
         network_type,
         address_type,
-        unicast_address: unicast_address.to_owned(),
+        unicast_address,
     };
 
     Ok((input, origin))
@@ -371,7 +535,9 @@ fn parse_connection_line<'a, E>(input: &'a str) -> nom::IResult<&'a str, Connect
 where
     E: ParseError<&'a str>
         + FromExternalError<&'a str, crate::EnumParseError>
-        + FromExternalError<&'a str, std::convert::Infallible>,
+        + FromExternalError<&'a str, std::convert::Infallible>
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, std::net::AddrParseError>,
 {
     let (input, _) = char('c')(input)?;
     let (input, _) = char('=')(input)?;
@@ -379,13 +545,30 @@ where
     let (input, _) = field_separator(input)?;
     let (input, address_type) = map_res(value_field, AddressType::from_str)(input)?;
     let (input, _) = field_separator(input)?;
-    let (input, connection_address) = value_field(input)?;
+    let (input, connection_address) = map_res(
+        take_till1(|c| c == ' ' || c == '/' || c == '\r' || c == '\n'),
+        |v| SdpAddress::parse(&address_type, v),
+    )(input)?;
+
+    // RFC 4566 multicast form: IP4 carries a mandatory TTL and an optional
+    // address count (`addr/ttl` or `addr/ttl/count`); IP6 has no TTL field,
+    // just an optional count (`addr/count`).
+    let (input, multicast_ttl) = match &address_type {
+        AddressType::Ip4 => opt(preceded(
+            char('/'),
+            map_res(take_till1(|c| c == '/' || c == ' ' || c == '\r' || c == '\n'), u8::from_str),
+        ))(input)?,
+        AddressType::Ip6 => (input, None),
+    };
+    let (input, multicast_count) = opt(preceded(char('/'), map_res(value_field, u32::from_str)))(input)?;
     let (input, _) = line_ending_or_eof(input)?;
 
     let connection = Connection {
         network_type,
         address_type,
-        connection_address: connection_address.to_owned(),
+        connection_address,
+        multicast_ttl,
+        multicast_count,
     };
 
     Ok((input, connection))
@@ -501,7 +684,9 @@ where
         + ContextError<&'a str>
         + FromExternalError<&'a str, crate::EnumParseError>
         + FromExternalError<&'a str, std::convert::Infallible>
-        + FromExternalError<&'a str, std::num::ParseIntError>,
+        + FromExternalError<&'a str, std::num::ParseIntError>
+        + FromExternalError<&'a str, std::net::AddrParseError>
+        + FromExternalError<&'a str, EncryptionKeyParseError>,
 {
     let (input, _) = char('m')(input)?;
     let (input, _) = char('=')(input)?;
@@ -515,9 +700,9 @@ where
     let (input, _) = line_ending_or_eof(input)?;
 
     let (input, title) = opt(parse_generic_line('i'))(input)?;
-    let (input, connection) = opt(parse_connection_line)(input)?;
+    let (input, connections) = many0(parse_connection_line)(input)?;
     let (input, bandwidths) = many0(parse_bandwidth_line)(input)?;
-    let (input, encryption_key) = opt(parse_generic_line('k'))(input)?;
+    let (input, encryption_key) = opt(parse_encryption_key_line)(input)?;
     let (input, parsed_attributes) = many0(parse_attribute_line)(input)?;
 
     let mut attributes = AttributeMap::new();
@@ -532,9 +717,9 @@ where
         protocol,
         formats: formats.into_iter().map(|s| s.to_owned()).collect(),
         title: title.map(|s| s.to_owned()),
-        connection,
+        connections,
         bandwidths: bandwidths.into_iter().collect(),
-        encryption_key: encryption_key.map(|s| s.to_owned()),
+        encryption_key,
         attributes,
     };
 
@@ -555,6 +740,88 @@ where
     }
 }
 
+fn parse_encryption_key_line<'a, E>(input: &'a str) -> nom::IResult<&'a str, EncryptionKey, E>
+where
+    E: ParseError<&'a str> + FromExternalError<&'a str, EncryptionKeyParseError>,
+{
+    let (input, _) = char('k')(input)?;
+    let (input, _) = char('=')(input)?;
+    let (input, encryption_key) = map_res(not_line_ending, EncryptionKey::parse)(input)?;
+    let (input, _) = line_ending_or_eof(input)?;
+
+    Ok((input, encryption_key))
+}
+
+/// A minimal RFC 4648 §4 base64 decoder (with padding): not worth pulling in
+/// a whole crate for the one `k=base64:...` payload we need to validate.
+fn base64_decode(value: &str) -> Option<Vec<u8>> {
+    fn digit(c: u8) -> Option<u8> {
+        match c {
+            b'A'..=b'Z' => Some(c - b'A'),
+            b'a'..=b'z' => Some(c - b'a' + 26),
+            b'0'..=b'9' => Some(c - b'0' + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let value = value.as_bytes();
+    if value.is_empty() || value.len() % 4 != 0 {
+        return None;
+    }
+
+    let mut result = Vec::with_capacity(value.len() / 4 * 3);
+
+    for chunk in value.chunks(4) {
+        let digits: Vec<u8> = chunk.iter().filter(|&&c| c != b'=').map(|&c| digit(c)).collect::<Option<_>>()?;
+
+        match digits.len() {
+            4 => {
+                result.push(digits[0] << 2 | digits[1] >> 4);
+                result.push(digits[1] << 4 | digits[2] >> 2);
+                result.push(digits[2] << 6 | digits[3]);
+            }
+            3 => {
+                result.push(digits[0] << 2 | digits[1] >> 4);
+                result.push(digits[1] << 4 | digits[2] >> 2);
+            }
+            2 => {
+                result.push(digits[0] << 2 | digits[1] >> 4);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(result)
+}
+
+/// The matching RFC 4648 §4 base64 encoder (with padding).
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = String::with_capacity((bytes.len() + 2) / 3 * 4);
+
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied();
+        let b2 = chunk.get(2).copied();
+
+        result.push(ALPHABET[(b0 >> 2) as usize] as char);
+        result.push(ALPHABET[((b0 & 0x03) << 4 | b1.unwrap_or(0) >> 4) as usize] as char);
+        result.push(match b1 {
+            Some(b1) => ALPHABET[((b1 & 0x0f) << 2 | b2.unwrap_or(0) >> 6) as usize] as char,
+            None => '=',
+        });
+        result.push(match b2 {
+            Some(b2) => ALPHABET[(b2 & 0x3f) as usize] as char,
+            None => '=',
+        });
+    }
+
+    result
+}
+
 fn sdp_time_field<'a, O, E>(input: &'a str) -> nom::IResult<&'a str, O, E>
 where
     O: std::ops::Mul<Output = O> + std::convert::From<u32> + std::str::FromStr<Err = std::num::ParseIntError>,
@@ -8,6 +8,8 @@ use super::*;
 
 const SDP_OFFER: &str = include_str!("../../resources/sdp-offer.txt");
 const SDP_ANSWER: &str = include_str!("../../resources/sdp-answer.txt");
+const SDP_OFFER_CHROME_SSRC: &str = include_str!("../../resources/sdp-offer-chrome-ssrc.txt");
+const SDP_OFFER_CHROME_RID: &str = include_str!("../../resources/sdp-offer-chrome-rid.txt");
 
 #[test]
 fn parse_offer() {
@@ -56,6 +58,27 @@ fn parse_and_serialize_answer() {
     assert_eq!(SDP_ANSWER, serialized);
 }
 
+// `AttributeMap` is a `ListOrderedMultimap` that tracks insertion order
+// across every key, not just within a key's own bucket, so a `Session`
+// parsed straight off the wire serializes back byte-for-byte without
+// reaching for the normalized `UnifiedBundleSession` domain layer at all.
+// This is the "preserve input ordering" path for relaying a remote SDP
+// unchanged; see `UnifiedBundleSession::to_sdp` for the canonical-ordering
+// path used when building a fresh offer/answer.
+#[test]
+fn parse_and_serialize_offer_chrome_ssrc() {
+    let parsed = Session::from_str(SDP_OFFER_CHROME_SSRC).unwrap();
+    let serialized = parsed.to_string();
+    assert_eq!(SDP_OFFER_CHROME_SSRC, serialized);
+}
+
+#[test]
+fn parse_and_serialize_offer_chrome_rid() {
+    let parsed = Session::from_str(SDP_OFFER_CHROME_RID).unwrap();
+    let serialized = parsed.to_string();
+    assert_eq!(SDP_OFFER_CHROME_RID, serialized);
+}
+
 #[test]
 fn unknown_attributes() {
     fn get_unknown(attributes: &AttributeMap) -> HashSet<String> {
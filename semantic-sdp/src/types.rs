@@ -1,29 +1,165 @@
 use std::borrow::Borrow;
 use std::str::FromStr;
 
-#[derive(Clone, Eq, PartialEq)]
-pub struct CertificateFingerprint(pub Vec<u8>);
+use crate::enums::FingerprintHashFunction;
+
+impl FingerprintHashFunction {
+    /// RFC 4572 digest length in bytes for this algorithm, where known.
+    /// `None` for `Unknown` functions, since we have no table to check against.
+    fn digest_len(&self) -> Option<usize> {
+        match self {
+            Self::Sha1 => Some(20),
+            Self::Sha224 => Some(28),
+            Self::Sha256 => Some(32),
+            Self::Sha384 => Some(48),
+            Self::Sha512 => Some(64),
+            Self::Md5 => Some(16),
+            Self::Md2 => Some(16),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+/// A DTLS certificate fingerprint: the hash algorithm plus the digest it
+/// produced, as carried together in SDP's `a=fingerprint:<hash-func>
+/// <hex:hex:...>` line (RFC 4572) or Jingle's `<fingerprint hash="...">`
+/// element (XEP-0320).
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct CertificateFingerprint {
+    pub hash_function: FingerprintHashFunction,
+    pub bytes: Vec<u8>,
+}
+
+impl CertificateFingerprint {
+    /// Builds a fingerprint from a bare colon-separated hex digest and an
+    /// already-known hash function, validating the digest length against it.
+    /// This is the form DTLS libraries typically hand back for "the
+    /// fingerprint of our own certificate under this hash function"; use
+    /// [`FromStr`](Self::from_str) instead when parsing a full SDP/Jingle
+    /// token that carries its own hash-function name alongside the digest.
+    pub fn from_hex_digest(hash_function: FingerprintHashFunction, hex: &str) -> Result<Self, CertificateFingerprintParseError> {
+        let bytes: Vec<u8> = hex
+            .split(':')
+            .map(|byte| u8::from_str_radix(byte, 16))
+            .collect::<Result<_, _>>()
+            .map_err(CertificateFingerprintParseError::InvalidDigest)?;
+
+        if let Some(expected) = hash_function.digest_len() {
+            if bytes.len() != expected {
+                return Err(CertificateFingerprintParseError::DigestLengthMismatch {
+                    expected,
+                    actual: bytes.len(),
+                });
+            }
+        }
+
+        Ok(Self { hash_function, bytes })
+    }
+
+    /// Just the colon-separated hex digest, without the hash-function name
+    /// that [`Display`](std::fmt::Display) includes alongside it.
+    pub fn digest_hex(&self) -> String {
+        self.bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":")
+    }
+
+    /// Hashes `cert_der` (a DER-encoded X.509 certificate) with `hash_function`.
+    ///
+    /// Returns `None` for [`FingerprintHashFunction::Md2`] or
+    /// [`FingerprintHashFunction::Unknown`] — there's no maintained digest
+    /// implementation for the former, and nothing to run at all for the
+    /// latter. `hash_function` ultimately comes from a remote peer's
+    /// `a=fingerprint` line, so this has to fail gracefully rather than
+    /// panic: a peer can put any token it likes there.
+    pub fn from_der(cert_der: &[u8], hash_function: FingerprintHashFunction) -> Option<Self> {
+        use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+        let bytes = match &hash_function {
+            FingerprintHashFunction::Sha1 => {
+                use sha1::Sha1;
+                Sha1::digest(cert_der).to_vec()
+            }
+            FingerprintHashFunction::Sha224 => Sha224::digest(cert_der).to_vec(),
+            FingerprintHashFunction::Sha256 => Sha256::digest(cert_der).to_vec(),
+            FingerprintHashFunction::Sha384 => Sha384::digest(cert_der).to_vec(),
+            FingerprintHashFunction::Sha512 => Sha512::digest(cert_der).to_vec(),
+            FingerprintHashFunction::Md5 => md5::compute(cert_der).0.to_vec(),
+            FingerprintHashFunction::Md2 | FingerprintHashFunction::Unknown(_) => return None,
+        };
+
+        Some(Self { hash_function, bytes })
+    }
+
+    /// Recomputes `cert_der`'s digest under this fingerprint's algorithm and
+    /// compares it against the stored bytes in constant time, to confirm a
+    /// DTLS peer's certificate actually matches the fingerprint advertised in
+    /// its SDP `a=fingerprint` line — the core security check of DTLS-SRTP —
+    /// without leaking digest bytes through a timing side channel. Returns
+    /// `false` (rather than panicking or erroring) if `self.hash_function`
+    /// isn't one we can compute, e.g. `Md2` or an `Unknown` token the remote
+    /// peer made up — such a fingerprint can never be verified, so it's
+    /// treated the same as a mismatch.
+    pub fn verify(&self, cert_der: &[u8]) -> bool {
+        match Self::from_der(cert_der, self.hash_function.clone()) {
+            Some(computed) => constant_time_eq(&self.bytes, &computed.bytes),
+            None => false,
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Error returned by [`CertificateFingerprint::from_str`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum CertificateFingerprintParseError {
+    /// The `<hash-func> <hex:hex:...>` token had no space separating the two halves.
+    MissingDigest,
+    /// `<hash-func>` couldn't be parsed as a [`FingerprintHashFunction`]. Not
+    /// produced by `from_str`, which maps unrecognized names to
+    /// [`FingerprintHashFunction::Unknown`] instead.
+    UnknownHashFunction(String),
+    /// The digest half wasn't valid colon-separated hex.
+    InvalidDigest(std::num::ParseIntError),
+    /// The digest's byte count didn't match what `hash_function` produces.
+    DigestLengthMismatch { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for CertificateFingerprintParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MissingDigest => write!(f, "missing fingerprint digest"),
+            Self::UnknownHashFunction(hash_function) => write!(f, "unknown fingerprint hash function {:?}", hash_function),
+            Self::InvalidDigest(error) => write!(f, "invalid fingerprint digest: {}", error),
+            Self::DigestLengthMismatch { expected, actual } => {
+                write!(f, "fingerprint digest should be {} bytes, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CertificateFingerprintParseError {}
 
 impl FromStr for CertificateFingerprint {
-    type Err = std::num::ParseIntError;
+    type Err = CertificateFingerprintParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let fingerprint: Result<Vec<_>, _> = s.split(':').map(|s| u8::from_str_radix(s, 16)).collect();
+        let (hash_function, digest) = s.split_once(' ').ok_or(CertificateFingerprintParseError::MissingDigest)?;
+
+        let hash_function = FingerprintHashFunction::from_str(&hash_function.to_ascii_lowercase())
+            .map_err(|_| CertificateFingerprintParseError::UnknownHashFunction(hash_function.to_owned()))?;
 
-        Ok(Self(fingerprint?))
+        Self::from_hex_digest(hash_function, digest)
     }
 }
 
 impl std::fmt::Display for CertificateFingerprint {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        let fingerprint = self
-            .0
-            .iter()
-            .map(|b| format!("{:02X}", b))
-            .collect::<Vec<_>>()
-            .join(":");
-
-        f.write_str(&fingerprint)
+        write!(f, "{} {}", self.hash_function, self.digest_hex())
     }
 }
 
@@ -33,7 +169,7 @@ impl std::fmt::Debug for CertificateFingerprint {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Ssrc(pub u32);
 
 impl FromStr for Ssrc {
@@ -56,7 +192,13 @@ impl From<u32> for Ssrc {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+impl AsRef<u32> for Ssrc {
+    fn as_ref(&self) -> &u32 {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Mid(pub String);
 
 impl From<&str> for Mid {
@@ -79,14 +221,24 @@ impl std::fmt::Display for Mid {
     }
 }
 
-// TODO: I'm not sure if this is the right trait to implement
+/// `Borrow`, not `AsRef`: `Hash`/`Eq`/`Ord` on the borrowed `&str` must agree
+/// with the owned `Mid`, which they do here since both just defer to the
+/// wrapped `String`. This is what lets a `HashMap<Mid, _>`/`BTreeMap<Mid, _>`
+/// be looked up with a bare `&str` key. For a cheap string view with no such
+/// promise, use [`AsRef<str>`](AsRef) instead.
 impl Borrow<str> for Mid {
     fn borrow(&self) -> &str {
         &self.0
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+impl AsRef<str> for Mid {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct Rid(pub String);
 
 impl From<&str> for Rid {
@@ -109,14 +261,20 @@ impl std::fmt::Display for Rid {
     }
 }
 
-// TODO: I'm not sure if this is the right trait to implement
+/// See [`Mid`]'s `Borrow<str>` impl for why this is `Borrow`, not `AsRef`.
 impl Borrow<str> for Rid {
     fn borrow(&self) -> &str {
         &self.0
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+impl AsRef<str> for Rid {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct PayloadType(pub u8);
 
 impl FromStr for PayloadType {
@@ -127,8 +285,70 @@ impl FromStr for PayloadType {
     }
 }
 
+impl AsRef<u8> for PayloadType {
+    fn as_ref(&self) -> &u8 {
+        &self.0
+    }
+}
+
 impl std::fmt::Display for PayloadType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         self.0.fmt(f)
     }
 }
+
+/// The `<unicast-address>`/`<connection-address>` field of an `o=` or `c=`
+/// line, validated against the `<addrtype>` (`IP4`/`IP6`) declared
+/// alongside it rather than left as a free-form string.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SdpAddress {
+    Ip(std::net::IpAddr),
+    Fqdn(String),
+}
+
+impl SdpAddress {
+    /// Parses `value` as the address type declared for it: `IP4`/`IP6`
+    /// fields must be a valid `Ipv4Addr`/`Ipv6Addr`, anything else is taken
+    /// to be an FQDN verbatim.
+    pub fn parse(address_type: &crate::enums::AddressType, value: &str) -> Result<Self, std::net::AddrParseError> {
+        use crate::enums::AddressType;
+
+        match address_type {
+            AddressType::Ip4 => Ok(Self::Ip(std::net::IpAddr::V4(value.parse()?))),
+            AddressType::Ip6 => Ok(Self::Ip(std::net::IpAddr::V6(value.parse()?))),
+        }
+    }
+}
+
+impl std::fmt::Display for SdpAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Ip(address) => address.fmt(f),
+            Self::Fqdn(fqdn) => fqdn.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Mid, Rid};
+
+    #[test]
+    fn test_mid_lookup_by_str() {
+        let mut map = HashMap::new();
+        map.insert(Mid::from("audio"), 1);
+
+        assert_eq!(map.get("audio"), Some(&1));
+    }
+
+    #[test]
+    fn test_rid_lookup_by_str() {
+        let mut map = HashMap::new();
+        map.insert(Rid::from("hi"), 1);
+
+        assert_eq!(map.get("hi"), Some(&1));
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use crate::types::{CertificateFingerprint, Mid, Ssrc};
+
+/// Assigns stable, consistently-mapped placeholders to privacy-sensitive SDP
+/// values so a session can be logged or attached to a bug report without
+/// leaking real network/identity information. The same input always maps to
+/// the same masked output within one `StatefulAnonymizer`, so cross-references
+/// (e.g. an SSRC appearing in both an `SsrcAttribute` and an `SsrcGroup`) stay
+/// consistent after anonymization.
+///
+/// [`UnifiedBundleSession::anonymized`](super::UnifiedBundleSession::anonymized)
+/// is the entry point that walks a whole session (ICE ufrag/pwd, DTLS
+/// fingerprints, candidate addresses, and per-media-description
+/// mids/CNAMEs/SSRCs) through one shared `StatefulAnonymizer`, returning a
+/// sanitized clone suitable for `Display`.
+///
+/// Modeled on the "stateful anonymizer" in Mozilla's webrtc-sdp.
+#[derive(Debug, Default)]
+pub struct StatefulAnonymizer {
+    ids: HashMap<u64, u64>,
+    ufrags: HashMap<String, String>,
+    pwds: HashMap<String, String>,
+    fingerprints: HashMap<CertificateFingerprint, CertificateFingerprint>,
+    addresses: HashMap<String, String>,
+    cnames: HashMap<String, String>,
+    ssrcs: HashMap<Ssrc, Ssrc>,
+    mids: HashMap<Mid, Mid>,
+}
+
+impl StatefulAnonymizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn anonymize_id(&mut self, id: u64) -> u64 {
+        let next = self.ids.len() as u64;
+        *self.ids.entry(id).or_insert(next)
+    }
+
+    pub fn anonymize_ufrag(&mut self, ufrag: &str) -> String {
+        let next = self.ufrags.len();
+        self.ufrags
+            .entry(ufrag.to_owned())
+            .or_insert_with(|| format!("ufrag-anonymized-{}", next))
+            .clone()
+    }
+
+    pub fn anonymize_pwd(&mut self, pwd: &str) -> String {
+        let next = self.pwds.len();
+        self.pwds
+            .entry(pwd.to_owned())
+            .or_insert_with(|| format!("pwd-anonymized-{}", next))
+            .clone()
+    }
+
+    pub fn anonymize_fingerprint(&mut self, fingerprint: &CertificateFingerprint) -> CertificateFingerprint {
+        let next = self.fingerprints.len();
+        self.fingerprints
+            .entry(fingerprint.clone())
+            .or_insert_with(|| CertificateFingerprint {
+                hash_function: fingerprint.hash_function.clone(),
+                bytes: vec![next as u8; fingerprint.bytes.len()],
+            })
+            .clone()
+    }
+
+    pub fn anonymize_address(&mut self, address: &str) -> String {
+        let next = self.addresses.len();
+        // 198.51.100.0/24 is reserved for documentation by RFC 5737, so it
+        // can't collide with anything real.
+        self.addresses
+            .entry(address.to_owned())
+            .or_insert_with(|| format!("198.51.100.{}", next % 256))
+            .clone()
+    }
+
+    pub fn anonymize_cname(&mut self, cname: &str) -> String {
+        let next = self.cnames.len();
+        self.cnames
+            .entry(cname.to_owned())
+            .or_insert_with(|| format!("cname-anonymized-{}", next))
+            .clone()
+    }
+
+    pub fn anonymize_ssrc(&mut self, ssrc: Ssrc) -> Ssrc {
+        let next = self.ssrcs.len() as u32;
+        *self.ssrcs.entry(ssrc).or_insert(Ssrc(next))
+    }
+
+    /// A media description's `mid` isn't itself private, but it's still
+    /// masked so a BUNDLE group's `a=group:BUNDLE` line doesn't give away
+    /// whether the original mids encoded anything meaningful (some
+    /// implementations use mids like `"microphone"` or a user id).
+    pub fn anonymize_mid(&mut self, mid: &Mid) -> Mid {
+        let next = self.mids.len();
+        self.mids
+            .entry(mid.clone())
+            .or_insert_with(|| Mid(format!("mid-{}", next)))
+            .clone()
+    }
+}
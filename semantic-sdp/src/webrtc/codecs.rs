@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use ordered_multimap::ListOrderedMultimap;
+
+use crate::enums::RtpCodecName;
+
+use super::RtpPayload;
+
+/// A single codec this server actually supports, consulted by
+/// [`RtpMediaDescription::answer`](super::RtpMediaDescription::answer) to
+/// compute the intersection with an offer's payload list instead of echoing
+/// it back verbatim. Modeled on gst-plugins-rs's `Codec` table.
+#[derive(Debug, Clone)]
+pub struct CodecCapability {
+    pub name: RtpCodecName,
+    pub clock: u32,
+    pub channels: Option<u8>,
+    /// fmtp parameters that must match the offer's value exactly for this
+    /// capability to match a payload (e.g. H264's `packetization-mode`/
+    /// `profile-level-id`). Parameters not listed here are carried through
+    /// from the offer unexamined (e.g. opus's `useinbandfec`).
+    pub required_parameters: HashMap<String, String>,
+    pub supported_feedback: ListOrderedMultimap<String, Option<String>>,
+    /// Whether to keep negotiating an RTX (`apt=`) companion payload for this
+    /// codec, when the offer included one.
+    pub rtx: bool,
+}
+
+impl CodecCapability {
+    pub fn new(name: RtpCodecName, clock: u32, channels: Option<u8>) -> Self {
+        Self {
+            name,
+            clock,
+            channels,
+            required_parameters: HashMap::new(),
+            supported_feedback: ListOrderedMultimap::new(),
+            rtx: false,
+        }
+    }
+
+    pub fn require_parameter(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.required_parameters.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn support_feedback(mut self, id: impl Into<String>, param: Option<impl Into<String>>) -> Self {
+        self.supported_feedback.append(id.into(), param.map(Into::into));
+        self
+    }
+
+    pub fn with_rtx(mut self) -> Self {
+        self.rtx = true;
+        self
+    }
+
+    fn matches(&self, payload: &RtpPayload) -> bool {
+        if self.name != payload.name || self.clock != payload.clock || self.channels != payload.channels {
+            return false;
+        }
+
+        self.required_parameters
+            .iter()
+            .all(|(key, value)| payload.parameters.get(key) == Some(value))
+    }
+}
+
+/// An ordered table of locally-supported codecs.
+#[derive(Debug, Clone, Default)]
+pub struct Codecs(Vec<CodecCapability>);
+
+impl Codecs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(mut self, codec: CodecCapability) -> Self {
+        self.0.push(codec);
+        self
+    }
+
+    fn find(&self, payload: &RtpPayload) -> Option<&CodecCapability> {
+        self.0.iter().find(|codec| codec.matches(payload))
+    }
+}
+
+/// Computes the intersection of `payloads` (as declared by a remote offer)
+/// against `codecs`, keeping the offer's payload type numbers but dropping
+/// anything we don't locally support, relaxing to what the matched
+/// [`CodecCapability`] actually supports for feedback and RTX.
+pub(super) fn negotiate_payloads(payloads: &[RtpPayload], codecs: &Codecs) -> Vec<RtpPayload> {
+    payloads
+        .iter()
+        .filter_map(|payload| {
+            let codec = codecs.find(payload)?;
+
+            let supported_feedback = payload
+                .supported_feedback
+                .iter()
+                .filter(|(id, param)| {
+                    codec
+                        .supported_feedback
+                        .iter()
+                        .any(|(codec_id, codec_param)| codec_id == *id && codec_param == *param)
+                })
+                .map(|(id, param)| (id.clone(), param.clone()))
+                .collect();
+
+            let rtx_payload_type = if codec.rtx { payload.rtx_payload_type } else { None };
+
+            Some(RtpPayload {
+                supported_feedback,
+                rtx_payload_type,
+                ..payload.clone()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,63 @@
+use super::RtpHeaderExtension;
+
+/// A header extension URI this server actually supports, consulted by
+/// [`RtpMediaDescription::answer`](super::RtpMediaDescription::answer) to
+/// compute the intersection with an offer's extension list instead of
+/// echoing it back verbatim. Modeled on gst-plugins-rs's extension table.
+#[derive(Debug, Clone)]
+pub struct ExtensionCapability {
+    pub uri: String,
+}
+
+impl ExtensionCapability {
+    pub fn new(uri: impl Into<String>) -> Self {
+        Self { uri: uri.into() }
+    }
+}
+
+/// Well-known header extension URIs, collected here so callers don't have to
+/// retype (or mistype) them.
+pub mod uri {
+    /// <https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01>
+    pub const TRANSPORT_WIDE_CC: &str = "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+    /// <https://datatracker.ietf.org/doc/html/rfc9143>
+    pub const MID: &str = "urn:ietf:params:rtp-hdrext:sdes:mid";
+    /// <https://datatracker.ietf.org/doc/html/rfc8852>
+    pub const RTP_STREAM_ID: &str = "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id";
+    pub const REPAIRED_RTP_STREAM_ID: &str = "urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id";
+    /// <http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time>
+    pub const ABS_SEND_TIME: &str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time";
+}
+
+/// An ordered table of locally-supported header extension URIs.
+#[derive(Debug, Clone, Default)]
+pub struct Extensions(Vec<ExtensionCapability>);
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(mut self, extension: ExtensionCapability) -> Self {
+        self.0.push(extension);
+        self
+    }
+
+    fn supports(&self, uri: &str) -> bool {
+        self.0.iter().any(|extension| extension.uri == uri)
+    }
+}
+
+/// Computes the intersection of `offered` (as declared by a remote offer)
+/// against `extensions`, keeping the offer's extension id numbers and
+/// dropping anything we don't locally support. Ids are never reassigned:
+/// since we only ever answer (never generate an initial offer) here, the
+/// offerer's numbering is already consistent and there is nothing to
+/// reconcile it against.
+pub(super) fn negotiate_extensions(offered: &[RtpHeaderExtension], extensions: &Extensions) -> Vec<RtpHeaderExtension> {
+    offered
+        .iter()
+        .filter(|extension| extensions.supports(&extension.uri))
+        .cloned()
+        .collect()
+}
@@ -10,18 +10,25 @@ use rand::Rng;
 
 use crate::attributes::{
     Candidate, ExtensionMap, ExtensionMapAllowMixed, Fingerprint, FormatParameters, Group, IceLite, IceOptions, IcePwd,
-    IceUfrag, Inactive, Mid, ReceiveOnly, Rid, Rtcp, RtcpFeedback, RtcpMux, RtcpMuxOnly, RtcpReducedSize, RtpMap,
-    SendOnly, SendReceive, Setup, SsrcAttribute, SsrcGroup,
+    IceUfrag, Inactive, MaxMessageSize, Mid, ReceiveOnly, Rid, Rtcp, RtcpFeedback, RtcpMux, RtcpMuxOnly,
+    RtcpReducedSize, RtpMap, SctpPort, SendOnly, SendReceive, Setup, Simulcast, SsrcAttribute, SsrcGroup,
 };
 use crate::enums::{
-    AddressType, BandwidthType, FingerprintHashFunction, GroupSemantics, IceOption, MediaType, NetworkType,
-    RidDirection, RtpCodecName, SetupRole, SsrcGroupSemantics, TransportProtocol,
+    AddressType, BandwidthType, ExtensionMapDirection, GroupSemantics, IceOption, MediaType, NetworkType, RidDirection,
+    RtpCodecName, SetupRole, SsrcGroupSemantics, TransportProtocol,
 };
-use crate::types::{CertificateFingerprint, PayloadType, Ssrc};
+use crate::types::{CertificateFingerprint, PayloadType, SdpAddress, Ssrc};
 use crate::{sdp, types, AttributeMap};
 
+mod anonymizer;
+mod codecs;
+mod extensions;
 mod tests;
 
+pub use anonymizer::StatefulAnonymizer;
+pub use codecs::{CodecCapability, Codecs};
+pub use extensions::{uri as extension_uri, ExtensionCapability, Extensions};
+
 /// Simplified SDP representation for a unified-plan WebRTC session with a single bundled transport.
 ///
 /// A lot of functionality is unable to be represented here, but it should have enough to negotiate
@@ -37,17 +44,15 @@ pub struct UnifiedBundleSession {
     pub ice_options: HashSet<IceOption>,
     pub candidates: Vec<Candidate>,
 
-    pub fingerprints: ListOrderedMultimap<FingerprintHashFunction, CertificateFingerprint>,
+    pub fingerprints: Vec<CertificateFingerprint>,
     pub setup_role: SetupRole,
 
     pub allow_mixed_extension_maps: bool,
 
-    // TODO: Support non-RTP media
-    pub media_descriptions: Vec<RtpMediaDescription>,
+    pub media_descriptions: Vec<MediaDescription>,
 }
 
 impl UnifiedBundleSession {
-    // TODO: This probably needs a builder class.
     pub fn new() -> UnifiedBundleSession {
         use rand::distributions::Alphanumeric;
         let mut rng = rand::thread_rng();
@@ -60,14 +65,106 @@ impl UnifiedBundleSession {
             ice_pwd: rng.sample_iter(Alphanumeric).take(24).collect(),
             ice_options: HashSet::new(),
             candidates: Vec::new(),
-            fingerprints: ListOrderedMultimap::new(),
+            fingerprints: Vec::new(),
             setup_role: SetupRole::ActivePassive,
             allow_mixed_extension_maps: true,
             media_descriptions: Vec::new(),
         }
     }
 
-    pub fn answer(&self) -> UnifiedBundleSession {
+    /// Appends a new RTP `m=` section and returns a mutable handle for
+    /// attaching payloads, encodings, header extensions, and bandwidths,
+    /// so an offer can be assembled without hand-rolling every
+    /// [`RtpMediaDescription`] field. Mirrors the ergonomics of Mozilla's
+    /// `SdpSession::add_media`.
+    ///
+    /// `mid` is assigned from the new section's index in
+    /// [`media_descriptions`](Self::media_descriptions). The `c=IN IP4
+    /// 0.0.0.0` boilerplate isn't parameterized here: once the session is
+    /// bundled, [`RtpMediaDescription::to_sdp`] always emits that
+    /// placeholder regardless of `port`, since the real transport address
+    /// comes from [`candidates`](Self::candidates), not the `m=`/`c=`
+    /// lines.
+    pub fn add_media(
+        &mut self,
+        kind: MediaType,
+        direction: MediaDirection,
+        port: u16,
+        protocol: TransportProtocol,
+    ) -> &mut RtpMediaDescription {
+        let mid = types::Mid(self.media_descriptions.len().to_string());
+
+        self.media_descriptions.push(MediaDescription::Rtp(RtpMediaDescription {
+            kind,
+            port,
+            protocol,
+            bandwidths: HashMap::new(),
+            mid,
+            payloads: Vec::new(),
+            direction,
+            encodings: Vec::new(),
+            redundancy: Vec::new(),
+            extensions: Vec::new(),
+            rtcp_mux: true,
+            rtcp_mux_only: false,
+            rtcp_reduced_size: false,
+            simulcast: None,
+        }));
+
+        self.media_descriptions
+            .last_mut()
+            .and_then(MediaDescription::as_rtp_mut)
+            .expect("just pushed an Rtp media description")
+    }
+
+    /// Returns a clone with all privacy-sensitive fields (ICE ufrag/pwd, DTLS
+    /// fingerprints, candidate connection addresses, CNAMEs, SSRCs, mids, and
+    /// the origin id/version) replaced by stable, consistently-mapped
+    /// placeholders, so the result is safe to paste into logs or a bug
+    /// report. See [`StatefulAnonymizer`].
+    pub fn anonymized(&self) -> UnifiedBundleSession {
+        let mut anonymizer = StatefulAnonymizer::new();
+
+        UnifiedBundleSession {
+            id: anonymizer.anonymize_id(self.id),
+            version: anonymizer.anonymize_id(self.version),
+            ice_lite: self.ice_lite,
+            ice_ufrag: anonymizer.anonymize_ufrag(&self.ice_ufrag),
+            ice_pwd: anonymizer.anonymize_pwd(&self.ice_pwd),
+            ice_options: self.ice_options.clone(),
+            candidates: self
+                .candidates
+                .iter()
+                .map(|candidate| Candidate {
+                    address: anonymizer.anonymize_address(&candidate.address),
+                    rel_addr: candidate
+                        .rel_addr
+                        .as_deref()
+                        .map(|address| anonymizer.anonymize_address(address)),
+                    ..candidate.clone()
+                })
+                .collect(),
+            fingerprints: self
+                .fingerprints
+                .iter()
+                .map(|fingerprint| anonymizer.anonymize_fingerprint(fingerprint))
+                .collect(),
+            setup_role: self.setup_role.clone(),
+            allow_mixed_extension_maps: self.allow_mixed_extension_maps,
+            media_descriptions: self
+                .media_descriptions
+                .iter()
+                .map(|md| md.anonymized(&mut anonymizer))
+                .collect(),
+        }
+    }
+
+    /// Builds an answer to this offer. `codecs` is consulted to negotiate
+    /// down each media description's payloads to what we actually support,
+    /// rather than echoing back every codec the offerer listed, and
+    /// `extensions` is consulted the same way for header extensions; see
+    /// [`RtpMediaDescription::answer`].
+    pub fn answer(&self, codecs: &Codecs, extensions: &Extensions) -> UnifiedBundleSession {
         use rand::distributions::Alphanumeric;
         let mut rng = rand::thread_rng();
 
@@ -79,10 +176,14 @@ impl UnifiedBundleSession {
             ice_pwd: rng.sample_iter(Alphanumeric).take(24).collect(),
             ice_options: HashSet::new(),
             candidates: Vec::new(),
-            fingerprints: ListOrderedMultimap::new(),
+            fingerprints: Vec::new(),
             setup_role: self.setup_role.reverse(),
             allow_mixed_extension_maps: self.allow_mixed_extension_maps,
-            media_descriptions: self.media_descriptions.iter().map(|md| md.answer()).collect(),
+            media_descriptions: self
+                .media_descriptions
+                .iter()
+                .map(|md| md.answer(codecs, extensions))
+                .collect(),
         }
     }
 
@@ -133,7 +234,10 @@ impl UnifiedBundleSession {
             .get_vec::<Fingerprint>()
             .into_iter()
             .chain(sdp.attributes.get_vec())
-            .map(|a| (a.hash_function.clone(), a.fingerprint.clone()))
+            .map(|a| CertificateFingerprint {
+                hash_function: a.hash_function.clone(),
+                bytes: a.fingerprint.clone(),
+            })
             .collect();
 
         let setup_role = first_media_description
@@ -152,7 +256,7 @@ impl UnifiedBundleSession {
         let media_descriptions = sdp
             .media_descriptions
             .iter()
-            .map(RtpMediaDescription::from_sdp)
+            .map(MediaDescription::from_sdp)
             .collect::<Result<Vec<_>, _>>()?;
 
         let session = UnifiedBundleSession {
@@ -172,6 +276,11 @@ impl UnifiedBundleSession {
         Ok(session)
     }
 
+    /// Rebuilds every attribute from this session's own canonical field
+    /// order, rather than any order a source SDP might have been parsed
+    /// from — appropriate for freshly-assembled offers/answers. To relay a
+    /// remote SDP with its original line order preserved instead, round-trip
+    /// through [`sdp::Session`] directly rather than through this type.
     pub fn to_sdp(&self) -> sdp::Session {
         let mut attributes = AttributeMap::new();
 
@@ -182,7 +291,7 @@ impl UnifiedBundleSession {
         if !self.media_descriptions.is_empty() {
             attributes.append(Group {
                 semantics: GroupSemantics::Bundle,
-                mids: self.media_descriptions.iter().map(|md| md.mid.clone()).collect(),
+                mids: self.media_descriptions.iter().map(|md| md.mid().clone()).collect(),
             });
 
             // TODO: msid-semantic ?
@@ -201,14 +310,14 @@ impl UnifiedBundleSession {
                 session_version: self.version,
                 network_type: NetworkType::Internet,
                 address_type: AddressType::Ip4,
-                unicast_address: "127.0.0.1".to_owned(),
+                unicast_address: SdpAddress::Ip(std::net::Ipv4Addr::LOCALHOST.into()),
             },
             name: None,
             information: None,
             uri: None,
             email_address: None,
             phone_number: None,
-            connection: None,
+            connections: Vec::new(),
             bandwidths: HashMap::new(),
             times: vec![sdp::Time {
                 start: 0,
@@ -258,6 +367,32 @@ impl MediaDirection {
     }
 }
 
+/// RFC 4145 §4.1 / RFC 5763 §5 DTLS setup role negotiation: `active` and
+/// `passive` swap, `actpass` (either side may initiate) resolves to `active`
+/// since an answer has to commit to one, and `holdconn` (no media) reverses
+/// to itself.
+impl SetupRole {
+    pub fn reverse(&self) -> SetupRole {
+        match self {
+            SetupRole::Active => SetupRole::Passive,
+            SetupRole::Passive => SetupRole::Active,
+            SetupRole::ActivePassive => SetupRole::Active,
+            SetupRole::HoldConnection => SetupRole::HoldConnection,
+        }
+    }
+}
+
+impl ExtensionMapDirection {
+    pub fn reverse(&self) -> ExtensionMapDirection {
+        match self {
+            ExtensionMapDirection::SendOnly => ExtensionMapDirection::ReceiveOnly,
+            ExtensionMapDirection::ReceiveOnly => ExtensionMapDirection::SendOnly,
+            ExtensionMapDirection::SendReceive => ExtensionMapDirection::SendReceive,
+            ExtensionMapDirection::Inactive => ExtensionMapDirection::Inactive,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct RtpPayload {
     pub payload_type: PayloadType,
@@ -274,12 +409,335 @@ pub enum RtpEncoding {
     Rid {
         rid: types::Rid,
         direction: RidDirection,
+        restrictions: Vec<RidRestriction>,
     },
     SendingSsrc {
         cname: String,
         ssrc: Ssrc,
         rtx_ssrc: Option<Ssrc>,
+        fec_ssrc: Option<Ssrc>,
+    },
+}
+
+/// A single negotiated `a=extmap` entry (RFC 8285). Unlike a bare id/uri
+/// pair, `direction` and `attributes` are preserved so offers from browsers
+/// that send directional or attributed extensions (e.g. Firefox) round-trip
+/// instead of being dropped.
+#[derive(Debug, Clone)]
+pub struct RtpHeaderExtension {
+    pub id: u16,
+    pub uri: String,
+    pub direction: Option<ExtensionMapDirection>,
+    pub attributes: Vec<String>,
+}
+
+/// A single `a=rid` restriction param. Well-known numeric restrictions (RFC
+/// 8851 §7) get typed variants so callers can inspect them without
+/// re-parsing; anything else round-trips through [`Other`](Self::Other).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RidRestriction {
+    /// The restricted payload type list (the `pt=` param).
+    Pt(Vec<PayloadType>),
+    MaxWidth(u32),
+    MaxHeight(u32),
+    MaxFps(u32),
+    MaxFs(u32),
+    MaxBr(u32),
+    MaxPps(u32),
+    Other(String, String),
+}
+
+fn rid_restrictions_from_attribute(attribute: &Rid) -> Vec<RidRestriction> {
+    let mut restrictions = Vec::new();
+
+    if !attribute.formats.is_empty() {
+        restrictions.push(RidRestriction::Pt(
+            attribute.formats.iter().map(|&pt| PayloadType(pt)).collect(),
+        ));
+    }
+
+    for (key, value) in &attribute.restrictions {
+        let restriction = match key.as_str() {
+            "max-width" => value.parse().ok().map(RidRestriction::MaxWidth),
+            "max-height" => value.parse().ok().map(RidRestriction::MaxHeight),
+            "max-fps" => value.parse().ok().map(RidRestriction::MaxFps),
+            "max-fs" => value.parse().ok().map(RidRestriction::MaxFs),
+            "max-br" => value.parse().ok().map(RidRestriction::MaxBr),
+            "max-pps" => value.parse().ok().map(RidRestriction::MaxPps),
+            _ => None,
+        }
+        .unwrap_or_else(|| RidRestriction::Other(key.clone(), value.clone()));
+
+        restrictions.push(restriction);
+    }
+
+    restrictions
+}
+
+fn rid_restrictions_to_attribute_parts(restrictions: &[RidRestriction]) -> (Vec<u8>, Vec<(String, String)>) {
+    let mut formats = Vec::new();
+    let mut other = Vec::new();
+
+    for restriction in restrictions {
+        match restriction {
+            RidRestriction::Pt(pts) => formats.extend(pts.iter().map(|pt| pt.0)),
+            RidRestriction::MaxWidth(value) => other.push(("max-width".to_owned(), value.to_string())),
+            RidRestriction::MaxHeight(value) => other.push(("max-height".to_owned(), value.to_string())),
+            RidRestriction::MaxFps(value) => other.push(("max-fps".to_owned(), value.to_string())),
+            RidRestriction::MaxFs(value) => other.push(("max-fs".to_owned(), value.to_string())),
+            RidRestriction::MaxBr(value) => other.push(("max-br".to_owned(), value.to_string())),
+            RidRestriction::MaxPps(value) => other.push(("max-pps".to_owned(), value.to_string())),
+            RidRestriction::Other(key, value) => other.push((key.clone(), value.clone())),
+        }
+    }
+
+    (formats, other)
+}
+
+/// A payload that doesn't carry media itself but protects one that does,
+/// parsed from the `a=rtpmap`/`a=fmtp` lines [`payloads`](RtpMediaDescription::payloads)
+/// filters out since they aren't themselves playable.
+#[derive(Debug, Clone)]
+pub enum RedundancyEncoding {
+    /// RFC 2198 RED. `levels` is the ordered list of payload types from the
+    /// `a=fmtp` value (e.g. `111/111` for a single redundant encoding of
+    /// payload 111), the primary encoding followed by each redundant one.
+    Red {
+        payload_type: PayloadType,
+        clock: u32,
+        levels: Vec<PayloadType>,
     },
+    /// RFC 5109 ulpfec, reconstructing `media_payload_type`.
+    UlpFec {
+        payload_type: PayloadType,
+        clock: u32,
+        media_payload_type: Option<PayloadType>,
+    },
+    /// draft-ietf-payload-flexible-fec-scheme flexfec, reconstructing
+    /// `media_payload_type`.
+    FlexFec {
+        payload_type: PayloadType,
+        clock: u32,
+        media_payload_type: Option<PayloadType>,
+    },
+}
+
+/// Parses the redundancy/FEC payloads out of `rtp_maps`, reusing the same
+/// `apt=` fmtp convention [`RtpPayload::rtx_payload_type`] is keyed off of to
+/// associate a ulpfec/flexfec payload with the media payload it protects.
+fn redundancy_encodings_from_attributes(
+    rtp_maps: &HashMap<PayloadType, &RtpMap>,
+    format_parameters: &HashMap<PayloadType, HashMap<String, String>>,
+    raw_format_parameters: &HashMap<PayloadType, &str>,
+) -> Vec<RedundancyEncoding> {
+    rtp_maps
+        .iter()
+        .filter_map(|(&fmt, map)| {
+            let media_payload_type = || {
+                format_parameters
+                    .get(&fmt)
+                    .and_then(|parameters| parameters.get("apt"))
+                    .and_then(|apt| PayloadType::from_str(apt).ok())
+            };
+
+            match map.name {
+                RtpCodecName::Red => {
+                    let levels = raw_format_parameters
+                        .get(&fmt)
+                        .map(|raw| {
+                            raw.split('/')
+                                .filter_map(|level| PayloadType::from_str(level.trim()).ok())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    Some(RedundancyEncoding::Red {
+                        payload_type: fmt,
+                        clock: map.clock,
+                        levels,
+                    })
+                }
+                RtpCodecName::UlpFec => Some(RedundancyEncoding::UlpFec {
+                    payload_type: fmt,
+                    clock: map.clock,
+                    media_payload_type: media_payload_type(),
+                }),
+                RtpCodecName::FlexFec => Some(RedundancyEncoding::FlexFec {
+                    payload_type: fmt,
+                    clock: map.clock,
+                    media_payload_type: media_payload_type(),
+                }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// A single negotiated `m=` section: either an RTP media track, or an SCTP
+/// association carrying WebRTC data channels. Both share the session's
+/// ICE/DTLS parameters and join the BUNDLE group via their `mid`.
+#[derive(Debug, Clone)]
+pub enum MediaDescription {
+    Rtp(RtpMediaDescription),
+    Application(SctpMediaDescription),
+}
+
+impl MediaDescription {
+    pub fn mid(&self) -> &types::Mid {
+        match self {
+            MediaDescription::Rtp(media_description) => &media_description.mid,
+            MediaDescription::Application(media_description) => &media_description.mid,
+        }
+    }
+
+    pub fn as_rtp(&self) -> Option<&RtpMediaDescription> {
+        match self {
+            MediaDescription::Rtp(media_description) => Some(media_description),
+            MediaDescription::Application(_) => None,
+        }
+    }
+
+    pub fn as_rtp_mut(&mut self) -> Option<&mut RtpMediaDescription> {
+        match self {
+            MediaDescription::Rtp(media_description) => Some(media_description),
+            MediaDescription::Application(_) => None,
+        }
+    }
+
+    pub fn as_application(&self) -> Option<&SctpMediaDescription> {
+        match self {
+            MediaDescription::Rtp(_) => None,
+            MediaDescription::Application(media_description) => Some(media_description),
+        }
+    }
+
+    fn anonymized(&self, anonymizer: &mut StatefulAnonymizer) -> MediaDescription {
+        match self {
+            MediaDescription::Rtp(media_description) => MediaDescription::Rtp(media_description.anonymized(anonymizer)),
+            MediaDescription::Application(media_description) => {
+                MediaDescription::Application(media_description.anonymized(anonymizer))
+            }
+        }
+    }
+
+    /// See [`RtpMediaDescription::answer`]. `codecs` and `extensions` are
+    /// ignored for SCTP associations, which are echoed back unchanged.
+    fn answer(&self, codecs: &Codecs, extensions: &Extensions) -> MediaDescription {
+        match self {
+            MediaDescription::Rtp(media_description) => {
+                MediaDescription::Rtp(media_description.answer(codecs, extensions))
+            }
+            MediaDescription::Application(media_description) => MediaDescription::Application(media_description.answer()),
+        }
+    }
+
+    fn from_sdp(sdp: &sdp::MediaDescription) -> Result<Self, String> {
+        match sdp.protocol {
+            TransportProtocol::UdpDtlsSctp | TransportProtocol::TcpDtlsSctp | TransportProtocol::DtlsSctp => {
+                SctpMediaDescription::from_sdp(sdp).map(MediaDescription::Application)
+            }
+            _ => RtpMediaDescription::from_sdp(sdp).map(MediaDescription::Rtp),
+        }
+    }
+
+    fn to_sdp(&self, session: &UnifiedBundleSession) -> sdp::MediaDescription {
+        match self {
+            MediaDescription::Rtp(media_description) => media_description.to_sdp(session),
+            MediaDescription::Application(media_description) => media_description.to_sdp(session),
+        }
+    }
+}
+
+/// An `m=application ... UDP/DTLS/SCTP webrtc-datachannel` section, carrying
+/// the SCTP association WebRTC data channels are multiplexed over.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc8841>
+#[derive(Debug, Clone)]
+pub struct SctpMediaDescription {
+    pub mid: types::Mid,
+    pub port: u16,
+    pub sctp_port: u16,
+    pub max_message_size: Option<u64>,
+}
+
+impl SctpMediaDescription {
+    fn answer(&self) -> SctpMediaDescription {
+        self.clone()
+    }
+
+    /// See [`UnifiedBundleSession::anonymized`]. `mid` is routed through
+    /// `anonymizer` so it stays consistent with the RTP media descriptions
+    /// anonymized as part of the same session.
+    fn anonymized(&self, anonymizer: &mut StatefulAnonymizer) -> SctpMediaDescription {
+        SctpMediaDescription {
+            mid: anonymizer.anonymize_mid(&self.mid),
+            ..self.clone()
+        }
+    }
+
+    fn from_sdp(sdp: &sdp::MediaDescription) -> Result<Self, String> {
+        let mid = sdp.attributes.get::<Mid>().ok_or("mid is required")?.0.clone();
+        let sctp_port = sdp.attributes.get::<SctpPort>().ok_or("sctp-port is required")?.0;
+        let max_message_size = sdp.attributes.get::<MaxMessageSize>().map(|a| a.0);
+
+        Ok(SctpMediaDescription {
+            mid,
+            port: sdp.port,
+            sctp_port,
+            max_message_size,
+        })
+    }
+
+    fn to_sdp(&self, session: &UnifiedBundleSession) -> sdp::MediaDescription {
+        let mut attributes = AttributeMap::new();
+
+        attributes.append(IceUfrag(session.ice_ufrag.clone()));
+        attributes.append(IcePwd(session.ice_pwd.clone()));
+
+        if !session.ice_options.is_empty() {
+            attributes.append(IceOptions(session.ice_options.clone()));
+        }
+
+        for candidate in &session.candidates {
+            attributes.append(candidate.clone());
+        }
+
+        for fingerprint in &session.fingerprints {
+            attributes.append(Fingerprint {
+                hash_function: fingerprint.hash_function.clone(),
+                fingerprint: fingerprint.bytes.clone(),
+            });
+        }
+
+        attributes.append(Setup(session.setup_role.clone()));
+
+        attributes.append(Mid(self.mid.clone()));
+
+        attributes.append(SctpPort(self.sctp_port));
+
+        if let Some(max_message_size) = self.max_message_size {
+            attributes.append(MaxMessageSize(max_message_size));
+        }
+
+        sdp::MediaDescription {
+            kind: MediaType::Application,
+            port: 9,
+            num_ports: None,
+            protocol: TransportProtocol::UdpDtlsSctp,
+            formats: vec!["webrtc-datachannel".to_owned()],
+            title: None,
+            connections: vec![sdp::Connection {
+                network_type: NetworkType::Internet,
+                address_type: AddressType::Ip4,
+                connection_address: SdpAddress::Ip(std::net::Ipv4Addr::UNSPECIFIED.into()),
+                multicast_ttl: None,
+                multicast_count: None,
+            }],
+            bandwidths: HashMap::new(),
+            encryption_key: None,
+            attributes,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -298,41 +756,116 @@ pub struct RtpMediaDescription {
 
     pub encodings: Vec<RtpEncoding>,
 
-    pub extensions: HashMap<String, u16>,
+    pub redundancy: Vec<RedundancyEncoding>,
+
+    pub extensions: Vec<RtpHeaderExtension>,
 
     pub rtcp_mux: bool,
     pub rtcp_mux_only: bool,
     pub rtcp_reduced_size: bool,
-    // msid, imageattr, simulcast
+
+    pub simulcast: Option<Simulcast>,
+    // msid, imageattr
 }
 
 impl RtpMediaDescription {
-    pub fn answer(&self) -> RtpMediaDescription {
+    /// Builds an answer to this (offered) media description. `codecs` is
+    /// intersected against [`payloads`](Self::payloads) to compute what
+    /// survives into the answer: payload type numbers are preserved from the
+    /// offer (since the offerer chose them), `supported_feedback` is
+    /// narrowed to what the matched [`CodecCapability`] also supports, and
+    /// `rtx_payload_type` is dropped for codecs we don't want to negotiate
+    /// RTX for. `extensions` is intersected the same way against
+    /// [`Self::extensions`], keeping each surviving entry's id but reversing
+    /// its direction.
+    pub fn answer(&self, codecs: &Codecs, extensions: &Extensions) -> RtpMediaDescription {
+        let payloads = codecs::negotiate_payloads(&self.payloads, codecs);
+
+        let extensions = extensions::negotiate_extensions(&self.extensions, extensions)
+            .into_iter()
+            .map(|extension| RtpHeaderExtension {
+                direction: extension.direction.as_ref().map(ExtensionMapDirection::reverse),
+                ..extension
+            })
+            .collect();
+
         let encodings = self
             .encodings
             .iter()
             .filter_map(|encoding| match encoding {
-                RtpEncoding::Rid { rid, direction } => Some(RtpEncoding::Rid {
+                RtpEncoding::Rid {
+                    rid,
+                    direction,
+                    restrictions,
+                } => Some(RtpEncoding::Rid {
                     rid: rid.clone(),
                     direction: direction.reverse(),
+                    restrictions: restrictions.clone(),
                 }),
                 RtpEncoding::SendingSsrc { .. } => None,
             })
             .collect();
 
+        let simulcast = self.simulcast.as_ref().map(|simulcast| Simulcast {
+            send: simulcast.receive.clone(),
+            receive: simulcast.send.clone(),
+        });
+
         RtpMediaDescription {
             kind: self.kind.clone(),
             port: self.port,
             protocol: self.protocol.clone(),
             bandwidths: HashMap::new(),
             mid: self.mid.clone(),
-            payloads: self.payloads.clone(),
+            payloads,
             direction: self.direction.reverse(),
             encodings,
-            extensions: self.extensions.clone(),
+            redundancy: self.redundancy.clone(),
+            extensions,
             rtcp_mux: self.rtcp_mux,
             rtcp_mux_only: self.rtcp_mux_only,
             rtcp_reduced_size: self.rtcp_reduced_size,
+            simulcast,
+        }
+    }
+
+    /// See [`UnifiedBundleSession::anonymized`]. `mid`, CNAMEs, and SSRCs
+    /// are routed through `anonymizer` so they stay consistent with any
+    /// other media description anonymized as part of the same session.
+    pub fn anonymized(&self, anonymizer: &mut StatefulAnonymizer) -> RtpMediaDescription {
+        let mid = anonymizer.anonymize_mid(&self.mid);
+
+        let encodings = self
+            .encodings
+            .iter()
+            .map(|encoding| match encoding {
+                RtpEncoding::Rid {
+                    rid,
+                    direction,
+                    restrictions,
+                } => RtpEncoding::Rid {
+                    rid: rid.clone(),
+                    direction: direction.clone(),
+                    restrictions: restrictions.clone(),
+                },
+                RtpEncoding::SendingSsrc {
+                    cname,
+                    ssrc,
+                    rtx_ssrc,
+                    fec_ssrc,
+                } => RtpEncoding::SendingSsrc {
+                    cname: anonymizer.anonymize_cname(cname),
+                    ssrc: anonymizer.anonymize_ssrc(*ssrc),
+                    rtx_ssrc: rtx_ssrc.map(|ssrc| anonymizer.anonymize_ssrc(ssrc)),
+                    fec_ssrc: fec_ssrc.map(|ssrc| anonymizer.anonymize_ssrc(ssrc)),
+                },
+            })
+            .collect();
+
+        RtpMediaDescription {
+            mid,
+            encodings,
+            ..self.clone()
         }
     }
 
@@ -352,21 +885,28 @@ impl RtpMediaDescription {
             .get_vec::<FormatParameters>()
             .into_iter()
             .map(|a| {
+                // A few codecs don't use the recommended key=value form, but we
+                // don't care about those so just ignore any without a value.
                 let parameters: HashMap<_, _> = a
-                    .parameters
-                    .split(';')
-                    .filter_map(|parameter| {
-                        // A few codecs don't use the recommended key=value form,
-                        // but we don't care about those so just ignore any without a '='.
-                        let (k, v) = parameter.split_at(parameter.find('=')?);
-                        Some((k.to_owned(), v[1..].to_owned()))
-                    })
+                    .parameters()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|(key, value)| Some((key.to_owned(), value?.to_owned())))
                     .collect();
 
                 (a.payload, parameters)
             })
             .collect();
 
+        // RED's fmtp value (e.g. `111/111`) isn't in the key=value form, so
+        // it's only reachable through the raw string.
+        let raw_format_parameters: HashMap<_, _> = sdp
+            .attributes
+            .get_vec::<FormatParameters>()
+            .into_iter()
+            .map(|a| (a.payload, a.raw()))
+            .collect();
+
         let rtx_payload_map: HashMap<_, _> = rtp_maps
             .iter()
             .filter_map(|(fmt, map)| {
@@ -382,6 +922,8 @@ impl RtpMediaDescription {
             })
             .collect();
 
+        let redundancy = redundancy_encodings_from_attributes(&rtp_maps, &format_parameters, &raw_format_parameters);
+
         let supported_feedback: ListOrderedMultimap<_, _> = sdp
             .attributes
             .get_vec::<RtcpFeedback>()
@@ -446,20 +988,39 @@ impl RtpMediaDescription {
 
         let mut encodings = Vec::new();
 
-        // TODO: Right now we ignore the simulcast attribute and just assume all RIDs are simulcast.
+        let simulcast = sdp.attributes.get::<Simulcast>().cloned();
 
-        let rid_encodings = sdp.attributes.get_vec::<Rid>().into_iter().filter_map(|attribute| {
-            if attribute.restrictions.is_some() {
-                // TODO: Restricted RIDs can't currently be represented.
-                return None;
+        if let Some(simulcast) = &simulcast {
+            // Every rid the simulcast attribute references for a direction must
+            // actually have a matching a=rid line, and vice versa: a rid we'd
+            // otherwise treat as simulcast but that isn't referenced by a=simulcast
+            // for its direction is rejected rather than silently guessed at.
+            let declared_rids: HashSet<_> = sdp.attributes.get_vec::<Rid>().into_iter().map(|a| &a.rid).collect();
+
+            for (direction, alternatives) in [(RidDirection::Send, &simulcast.send), (RidDirection::Receive, &simulcast.receive)] {
+                for rid in alternatives.iter().flatten() {
+                    if !declared_rids.contains(&rid.rid) {
+                        return Err(format!("simulcast {} rid {} has no matching a=rid line", direction, rid.rid));
+                    }
+                }
             }
 
-            let rid_encoding = RtpEncoding::Rid {
-                rid: attribute.rid.clone(),
-                direction: attribute.direction.clone(),
-            };
+            for rid in sdp.attributes.get_vec::<Rid>() {
+                let alternatives = match rid.direction {
+                    RidDirection::Send => &simulcast.send,
+                    RidDirection::Receive => &simulcast.receive,
+                };
 
-            Some(rid_encoding)
+                if !alternatives.iter().flatten().any(|alternative| alternative.rid == rid.rid) {
+                    return Err(format!("rid {} ({}) is not referenced by a=simulcast for its direction", rid.rid, rid.direction));
+                }
+            }
+        }
+
+        let rid_encodings = sdp.attributes.get_vec::<Rid>().into_iter().map(|attribute| RtpEncoding::Rid {
+            rid: attribute.rid.clone(),
+            direction: attribute.direction.clone(),
+            restrictions: rid_restrictions_from_attribute(attribute),
         });
 
         encodings.extend(rid_encodings);
@@ -495,31 +1056,40 @@ impl RtpMediaDescription {
             None => (ssrc_attributes.keys().next().cloned(), None),
         };
 
+        let fec_fr_group = ssrc_groups.get(&SsrcGroupSemantics::ForwardErrorCorrectionFlowReduced);
+        let fec_ssrc = fec_fr_group
+            .filter(|group| group.first() == ssrc.as_ref())
+            .and_then(|group| group.get(1).cloned());
+
         let cname = ssrc.and_then(|ssrc| ssrc_attributes.get(&ssrc)?.get("cname")?.clone());
 
         if let (Some(ssrc), Some(cname)) = (ssrc, cname) {
-            let ssrc_encoding = RtpEncoding::SendingSsrc { cname, ssrc, rtx_ssrc };
+            let ssrc_encoding = RtpEncoding::SendingSsrc {
+                cname,
+                ssrc,
+                rtx_ssrc,
+                fec_ssrc,
+            };
 
             encodings.push(ssrc_encoding);
         }
 
-        let extensions = sdp
-            .attributes
-            .get_vec::<ExtensionMap>()
-            .into_iter()
-            .filter_map(|map| {
-                if map.direction.is_some() {
-                    // Directional extensions are not supported.
-                    // TODO: Firefox uses them.
-                    return None;
-                }
+        let extension_maps: Vec<_> = sdp.attributes.get_vec::<ExtensionMap>().into_iter().collect();
 
-                if !map.attributes.is_empty() {
-                    // Extensions with attributes are not supported.
-                    return None;
-                }
+        let mut seen_extension_ids = HashSet::new();
+        for map in &extension_maps {
+            if !seen_extension_ids.insert(map.id) {
+                return Err(format!("duplicate extmap id {} in m-line", map.id));
+            }
+        }
 
-                Some((map.extension.clone(), map.id))
+        let extensions = extension_maps
+            .into_iter()
+            .map(|map| RtpHeaderExtension {
+                id: map.id,
+                uri: map.extension.clone(),
+                direction: map.direction.clone(),
+                attributes: map.attributes.clone(),
             })
             .collect();
 
@@ -532,10 +1102,12 @@ impl RtpMediaDescription {
             payloads,
             direction,
             encodings,
+            redundancy,
             extensions,
             rtcp_mux,
             rtcp_mux_only,
             rtcp_reduced_size,
+            simulcast,
         };
 
         Ok(media_description)
@@ -562,10 +1134,10 @@ impl RtpMediaDescription {
             attributes.append(candidate.clone());
         }
 
-        for (hash_function, fingerprint) in &session.fingerprints {
+        for fingerprint in &session.fingerprints {
             attributes.append(Fingerprint {
-                hash_function: hash_function.clone(),
-                fingerprint: fingerprint.clone(),
+                hash_function: fingerprint.hash_function.clone(),
+                fingerprint: fingerprint.bytes.clone(),
             });
         }
 
@@ -573,12 +1145,12 @@ impl RtpMediaDescription {
 
         attributes.append(Mid(self.mid.clone()));
 
-        for (extension, &id) in &self.extensions {
+        for extension in &self.extensions {
             attributes.append(ExtensionMap {
-                id,
-                direction: None,
-                extension: extension.clone(),
-                attributes: Vec::new(),
+                id: extension.id,
+                direction: extension.direction.clone(),
+                extension: extension.uri.clone(),
+                attributes: extension.attributes.clone(),
             });
         }
 
@@ -624,17 +1196,12 @@ impl RtpMediaDescription {
             }
 
             if !payload.parameters.is_empty() {
-                let parameters = payload
-                    .parameters
-                    .iter()
-                    .map(|(k, v)| format!("{}={}", k, v))
-                    .collect::<Vec<_>>()
-                    .join(";");
-
-                attributes.append(FormatParameters {
-                    payload: payload.payload_type,
-                    parameters,
-                });
+                let mut parameters = FormatParameters::new(payload.payload_type);
+                for (key, value) in &payload.parameters {
+                    parameters.set(key, Some(value.clone()));
+                }
+
+                attributes.append(parameters);
             }
 
             if let Some(rtx_payload_type) = payload.rtx_payload_type {
@@ -647,23 +1214,92 @@ impl RtpMediaDescription {
                     channels: payload.channels,
                 });
 
-                attributes.append(FormatParameters {
-                    payload: rtx_payload_type,
-                    parameters: format!("apt={}", payload.payload_type),
-                });
+                let mut parameters = FormatParameters::new(rtx_payload_type);
+                parameters.set("apt", Some(payload.payload_type.to_string()));
+
+                attributes.append(parameters);
+            }
+        }
+
+        for redundancy in &self.redundancy {
+            let (payload_type, clock, name, parameters) = match redundancy {
+                RedundancyEncoding::Red {
+                    payload_type,
+                    clock,
+                    levels,
+                } => {
+                    let mut parameters = FormatParameters::new(payload_type.0);
+                    let levels = levels.iter().map(|level| level.to_string()).collect::<Vec<_>>().join("/");
+                    parameters.set(&levels, None);
+
+                    (*payload_type, *clock, RtpCodecName::Red, Some(parameters))
+                }
+                RedundancyEncoding::UlpFec {
+                    payload_type,
+                    clock,
+                    media_payload_type,
+                } => (
+                    *payload_type,
+                    *clock,
+                    RtpCodecName::UlpFec,
+                    media_payload_type.map(|apt| {
+                        let mut parameters = FormatParameters::new(payload_type.0);
+                        parameters.set("apt", Some(apt.to_string()));
+                        parameters
+                    }),
+                ),
+                RedundancyEncoding::FlexFec {
+                    payload_type,
+                    clock,
+                    media_payload_type,
+                } => (
+                    *payload_type,
+                    *clock,
+                    RtpCodecName::FlexFec,
+                    media_payload_type.map(|apt| {
+                        let mut parameters = FormatParameters::new(payload_type.0);
+                        parameters.set("apt", Some(apt.to_string()));
+                        parameters
+                    }),
+                ),
+            };
+
+            formats.push(payload_type.to_string());
+
+            attributes.append(RtpMap {
+                payload: payload_type,
+                name,
+                clock,
+                channels: None,
+            });
+
+            if let Some(parameters) = parameters {
+                attributes.append(parameters);
             }
         }
 
         for encoding in &self.encodings {
             match encoding {
-                RtpEncoding::Rid { rid, direction } => {
+                RtpEncoding::Rid {
+                    rid,
+                    direction,
+                    restrictions,
+                } => {
+                    let (formats, restrictions) = rid_restrictions_to_attribute_parts(restrictions);
+
                     attributes.append(Rid {
                         rid: rid.clone(),
                         direction: direction.clone(),
-                        restrictions: None,
+                        formats,
+                        restrictions,
                     });
                 }
-                RtpEncoding::SendingSsrc { cname, ssrc, rtx_ssrc } => {
+                RtpEncoding::SendingSsrc {
+                    cname,
+                    ssrc,
+                    rtx_ssrc,
+                    fec_ssrc,
+                } => {
                     attributes.append(SsrcAttribute {
                         ssrc: *ssrc,
                         name: "cname".to_owned(),
@@ -685,66 +1321,41 @@ impl RtpMediaDescription {
                             ssrcs: vec![*ssrc, *rtx_ssrc],
                         });
                     }
-                }
-            }
-        }
-
-        let mut simulcast_value = String::new();
-
-        let send_rid_encodings: Vec<_> = self
-            .encodings
-            .iter()
-            .filter_map(|e| match e {
-                RtpEncoding::Rid {
-                    rid,
-                    direction: RidDirection::Send,
-                } => Some(rid.0.clone()),
-                _ => None,
-            })
-            .collect();
-
-        if !send_rid_encodings.is_empty() {
-            simulcast_value += &format!("send {}", send_rid_encodings.join(";"))
-        }
 
-        let recv_rid_encodings: Vec<_> = self
-            .encodings
-            .iter()
-            .filter_map(|e| match e {
-                RtpEncoding::Rid {
-                    rid,
-                    direction: RidDirection::Receive,
-                } => Some(rid.0.clone()),
-                _ => None,
-            })
-            .collect();
+                    if let Some(fec_ssrc) = fec_ssrc {
+                        attributes.append(SsrcAttribute {
+                            ssrc: *fec_ssrc,
+                            name: "cname".to_owned(),
+                            value: Some(cname.clone()),
+                        });
 
-        if !recv_rid_encodings.is_empty() {
-            if !send_rid_encodings.is_empty() {
-                simulcast_value += " ";
+                        attributes.append(SsrcGroup {
+                            semantics: SsrcGroupSemantics::ForwardErrorCorrectionFlowReduced,
+                            ssrcs: vec![*ssrc, *fec_ssrc],
+                        });
+                    }
+                }
             }
-
-            simulcast_value += &format!("recv {}", recv_rid_encodings.join(";"))
         }
 
-        if !simulcast_value.is_empty() {
-            // TODO: We haven't implemented a type for this attribute yet,
-            //       as the full parsing of it is fairly complex.
-            attributes.append_unknown("simulcast", Some(simulcast_value)).unwrap();
+        if let Some(simulcast) = &self.simulcast {
+            attributes.append(simulcast.clone());
         }
 
         sdp::MediaDescription {
             kind: self.kind.clone(),
-            port: 9,
+            port: self.port,
             num_ports: None,
             protocol: self.protocol.clone(),
             formats,
             title: None,
-            connection: Some(sdp::Connection {
+            connections: vec![sdp::Connection {
                 network_type: NetworkType::Internet,
                 address_type: AddressType::Ip4,
-                connection_address: "0.0.0.0".to_owned(),
-            }),
+                connection_address: SdpAddress::Ip(std::net::Ipv4Addr::UNSPECIFIED.into()),
+                multicast_ttl: None,
+                multicast_count: None,
+            }],
             bandwidths: self.bandwidths.clone(),
             encryption_key: None,
             attributes,
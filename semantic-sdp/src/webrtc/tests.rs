@@ -34,6 +34,13 @@ fn parse_offer_chrome_ssrc() {
     println!("{:#?}", session);
 }
 
+// `UnifiedBundleSession` always rebuilds attributes in its own canonical
+// order rather than replaying the order they were parsed in, so this is
+// expected to keep failing even though the underlying attribute lines all
+// round-trip correctly. Use `sdp::Session::from_str`/`to_string` directly
+// (see the `parse_and_serialize_offer_chrome_ssrc` test in `sdp::tests`)
+// when the original line order needs to be preserved, e.g. relaying a
+// remote SDP unchanged.
 #[test]
 #[ignore]
 fn parse_and_serialize_offer_chrome_ssrc() {
@@ -53,6 +60,7 @@ fn parse_offer_chrome_rid() {
     println!("{:#?}", session);
 }
 
+// See the comment on `parse_and_serialize_offer_chrome_ssrc` above.
 #[test]
 #[ignore]
 fn parse_and_serialize_offer_chrome_rid() {
@@ -62,24 +70,38 @@ fn parse_and_serialize_offer_chrome_rid() {
     assert_eq!(SDP_OFFER_CHROME_RID, serialized);
 }
 
+fn test_codecs() -> Codecs {
+    Codecs::new()
+        .add(CodecCapability::new(RtpCodecName::Opus, 48000, Some(2)))
+        .add(CodecCapability::new(RtpCodecName::Vp8, 90000, None).with_rtx())
+        .add(CodecCapability::new(RtpCodecName::H264, 90000, None).with_rtx())
+}
+
+fn test_extensions() -> Extensions {
+    Extensions::new()
+        .add(ExtensionCapability::new(extension_uri::MID))
+        .add(ExtensionCapability::new(extension_uri::RTP_STREAM_ID))
+        .add(ExtensionCapability::new(extension_uri::TRANSPORT_WIDE_CC))
+}
+
 #[test]
 fn answer_offer() {
     let offer = UnifiedBundleSession::from_str(SDP_OFFER).unwrap();
-    let answer = offer.answer();
+    let answer = offer.answer(&test_codecs(), &test_extensions());
     println!("{}", answer);
 }
 
 #[test]
 fn answer_offer_chrome_ssrc() {
     let offer = UnifiedBundleSession::from_str(SDP_OFFER_CHROME_SSRC).unwrap();
-    let answer = offer.answer();
+    let answer = offer.answer(&test_codecs(), &test_extensions());
     println!("{}", answer);
 }
 
 #[test]
 fn answer_offer_chrome_rid() {
     let offer = UnifiedBundleSession::from_str(SDP_OFFER_CHROME_RID).unwrap();
-    let answer = offer.answer();
+    let answer = offer.answer(&test_codecs(), &test_extensions());
     println!("{}", answer);
 }
 
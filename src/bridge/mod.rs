@@ -21,11 +21,23 @@ mod ffi {
         Failed,
     }
 
+    #[repr(i32)]
+    enum LogLevel {
+        Error,
+        Warning,
+        Info,
+        Debug,
+        UltraDebug,
+    }
+
     extern "Rust" {
         type DtlsIceTransportListenerRustAdapter;
         fn on_ice_timeout(self: &mut DtlsIceTransportListenerRustAdapter);
         fn on_dtls_state_changed(self: &mut DtlsIceTransportListenerRustAdapter, state: DtlsIceTransportDtlsState);
         fn on_remote_ice_candidate_activated(self: &mut DtlsIceTransportListenerRustAdapter, ip: &str, port: u16, priority: u32);
+        fn on_local_ice_candidate(self: &mut DtlsIceTransportListenerRustAdapter, foundation: &str, component: u16, ip: &str, port: u16, priority: u32, typ: &str);
+
+        fn dispatch_log_record(level: LogLevel, category: &str, message: &str);
     }
 
     unsafe extern "C++" {
@@ -33,10 +45,9 @@ mod ffi {
 
         type DtlsConnectionHash;
         type DtlsIceTransportDtlsState;
+        type LogLevel;
 
-        fn logger_enable_log(flag: bool);
-        fn logger_enable_debug(flag: bool);
-        fn logger_enable_ultra_debug(flag: bool);
+        fn logger_set_level(level: LogLevel);
 
         fn openssl_class_init() -> Result<()>;
 
@@ -75,11 +86,31 @@ impl std::fmt::Debug for DtlsIceTransportDtlsState {
     }
 }
 
+impl std::fmt::Debug for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            &LogLevel::Error => f.write_str("Error"),
+            &LogLevel::Warning => f.write_str("Warning"),
+            &LogLevel::Info => f.write_str("Info"),
+            &LogLevel::Debug => f.write_str("Debug"),
+            &LogLevel::UltraDebug => f.write_str("UltraDebug"),
+            _ => f.write_str("Unknown"),
+        }
+    }
+}
+
+/// Called by the C++ side for every log record; forwards to whatever sink
+/// was registered with [`crate::logging::set_sink`].
+fn dispatch_log_record(level: LogLevel, category: &str, message: &str) {
+    crate::logging::dispatch(level, category, message)
+}
+
 #[allow(unused_variables)]
 pub trait DtlsIceTransportListener: Send {
     fn on_ice_timeout(&mut self) {}
     fn on_dtls_state_changed(&mut self, state: DtlsIceTransportDtlsState) {}
     fn on_remote_ice_candidate_activated(&mut self, ip: &str, port: u16, priority: u32) {}
+    fn on_local_ice_candidate(&mut self, foundation: &str, component: u16, ip: &str, port: u16, priority: u32, typ: &str) {}
 }
 
 pub struct DtlsIceTransportListenerRustAdapter(Box<dyn DtlsIceTransportListener>);
@@ -96,6 +127,10 @@ impl DtlsIceTransportListenerRustAdapter {
     fn on_remote_ice_candidate_activated(&mut self, ip: &str, port: u16, priority: u32) {
         self.0.on_remote_ice_candidate_activated(ip, port, priority)
     }
+
+    fn on_local_ice_candidate(&mut self, foundation: &str, component: u16, ip: &str, port: u16, priority: u32, typ: &str) {
+        self.0.on_local_ice_candidate(foundation, component, ip, port, priority, typ)
+    }
 }
 
 impl<T> From<T> for DtlsIceTransportListenerRustAdapter where T: 'static + DtlsIceTransportListener {
@@ -0,0 +1,440 @@
+//! Conversion between Jingle (XEP-0166/0176/0167) XML elements and the
+//! internal [`sdp`](crate::sdp)/[`jsep`](crate::sdp::jsep) representation, for
+//! XMPP/Jitsi-style signaling.
+//!
+//! Like [`crate::sdp`], this is deliberately not a general-purpose XML
+//! library: it only understands the handful of elements/attributes Jingle
+//! uses for ICE-UDP transports and RTP descriptions, found with plain
+//! substring scanning rather than a real parser.
+
+use crate::ice::IceCandidateType;
+use crate::sdp::jsep::NegotiatedSession;
+use crate::sdp::{Direction, MediaType, OfferedCodec, RtcpFeedback, RtcpFeedbackType, SetupRole};
+
+/// One `<candidate/>` child of a `jingle-ice-udp` transport element.
+#[derive(Debug, Clone)]
+pub struct JingleCandidate {
+    pub foundation: String,
+    pub component: u16,
+    pub ip: String,
+    pub port: u16,
+    pub priority: u32,
+    pub kind: IceCandidateType,
+}
+
+/// The `<fingerprint/>` child of a transport element (XEP-0320).
+#[derive(Debug, Clone)]
+pub struct JingleFingerprint {
+    pub hash: String,
+    pub value: String,
+    pub setup: SetupRole,
+}
+
+/// A `jingle-ice-udp` `<transport/>` element.
+#[derive(Debug, Clone)]
+pub struct JingleTransport {
+    pub ufrag: String,
+    pub pwd: String,
+    pub candidates: Vec<JingleCandidate>,
+    pub fingerprint: Option<JingleFingerprint>,
+}
+
+/// One `<payload-type/>` child of a `jingle-rtp` description.
+#[derive(Debug, Clone)]
+pub struct JinglePayloadType {
+    pub id: u8,
+    pub name: String,
+    pub clock_rate: u32,
+    pub channels: Option<u8>,
+    pub feedback: Vec<RtcpFeedback>,
+}
+
+/// A `jingle-rtp` `<description/>` element.
+#[derive(Debug, Clone)]
+pub struct JingleDescription {
+    pub media: MediaType,
+    pub payload_types: Vec<JinglePayloadType>,
+}
+
+/// One `<content/>` element: a media description paired with its transport.
+#[derive(Debug, Clone)]
+pub struct JingleContent {
+    pub name: String,
+    pub senders: Direction,
+    pub description: JingleDescription,
+    pub transport: JingleTransport,
+}
+
+/// A `<group semantics="BUNDLE"/>` element, listing the bundled content names.
+#[derive(Debug, Clone)]
+pub struct JingleGroup {
+    pub semantics: String,
+    pub contents: Vec<String>,
+}
+
+fn attr<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let needle = format!("{}=\"", name);
+    let start = element.find(&needle)? + needle.len();
+    let end = element[start..].find('"')? + start;
+    Some(&element[start..end])
+}
+
+/// Scans `xml` for every `<tag ...>...</tag>` or `<tag .../>` element,
+/// returning each element's full text (including its tags). Matches are found
+/// at any nesting depth; the scan resumes after the end of each match.
+fn extract_elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let open_prefix = format!("<{}", tag);
+    let close_tag = format!("</{}>", tag);
+    let mut elements = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find(&open_prefix) {
+        let after_open = &rest[start..];
+
+        // Don't let e.g. "<candidate" match inside "<candidate-extension".
+        match after_open[open_prefix.len()..].chars().next() {
+            Some(' ') | Some('>') | Some('/') => (),
+            _ => {
+                rest = &after_open[open_prefix.len()..];
+                continue;
+            }
+        }
+
+        let tag_end = match after_open.find('>') {
+            Some(index) => index + 1,
+            None => break,
+        };
+
+        if after_open.as_bytes()[tag_end - 2] == b'/' {
+            elements.push(&after_open[..tag_end]);
+            rest = &after_open[tag_end..];
+        } else if let Some(close) = after_open.find(&close_tag) {
+            elements.push(&after_open[..close + close_tag.len()]);
+            rest = &after_open[close + close_tag.len()..];
+        } else {
+            break;
+        }
+    }
+
+    elements
+}
+
+fn candidate_type(value: &str) -> IceCandidateType {
+    match value {
+        "srflx" => IceCandidateType::ServerReflexive,
+        "relay" => IceCandidateType::Relay,
+        _ => IceCandidateType::Host,
+    }
+}
+
+impl JingleCandidate {
+    pub fn from_xml(element: &str) -> Option<JingleCandidate> {
+        Some(JingleCandidate {
+            foundation: attr(element, "foundation")?.to_owned(),
+            component: attr(element, "component")?.parse().ok()?,
+            ip: attr(element, "ip")?.to_owned(),
+            port: attr(element, "port")?.parse().ok()?,
+            priority: attr(element, "priority")?.parse().ok()?,
+            kind: candidate_type(attr(element, "type")?),
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        format!(
+            r#"<candidate foundation="{}" component="{}" ip="{}" port="{}" priority="{}" protocol="udp" type="{}"/>"#,
+            self.foundation,
+            self.component,
+            self.ip,
+            self.port,
+            self.priority,
+            self.kind.as_str(),
+        )
+    }
+}
+
+impl JingleFingerprint {
+    pub fn from_xml(element: &str) -> Option<JingleFingerprint> {
+        use std::str::FromStr;
+
+        let start = element.find('>')? + 1;
+        let end = element.find("</fingerprint>")?;
+
+        Some(JingleFingerprint {
+            hash: attr(element, "hash")?.to_owned(),
+            value: element[start..end].trim().to_owned(),
+            setup: SetupRole::from_str(attr(element, "setup")?).ok()?,
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        format!(
+            r#"<fingerprint xmlns="urn:xmpp:jingle:apps:dtls:0" hash="{}" setup="{}">{}</fingerprint>"#,
+            self.hash, self.setup, self.value,
+        )
+    }
+}
+
+impl JingleTransport {
+    pub fn from_xml(element: &str) -> Option<JingleTransport> {
+        Some(JingleTransport {
+            ufrag: attr(element, "ufrag")?.to_owned(),
+            pwd: attr(element, "pwd")?.to_owned(),
+            candidates: extract_elements(element, "candidate")
+                .into_iter()
+                .filter_map(JingleCandidate::from_xml)
+                .collect(),
+            fingerprint: extract_elements(element, "fingerprint")
+                .into_iter()
+                .next()
+                .and_then(JingleFingerprint::from_xml),
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let candidates = self.candidates.iter().map(JingleCandidate::to_xml).collect::<Vec<_>>().join("");
+        let fingerprint = self.fingerprint.as_ref().map(JingleFingerprint::to_xml).unwrap_or_default();
+
+        format!(
+            r#"<transport xmlns="urn:xmpp:jingle:transports:ice-udp:1" ufrag="{}" pwd="{}">{}{}</transport>"#,
+            self.ufrag, self.pwd, candidates, fingerprint,
+        )
+    }
+}
+
+impl JinglePayloadType {
+    pub fn from_xml(element: &str) -> Option<JinglePayloadType> {
+        let feedback = extract_elements(element, "rtcp-fb")
+            .into_iter()
+            .filter_map(|rtcp_fb| {
+                use std::str::FromStr;
+
+                let kind = attr(rtcp_fb, "type")?;
+                Some(RtcpFeedback {
+                    kind: RtcpFeedbackType::from_str(kind).unwrap_or(RtcpFeedbackType::Unknown(kind.to_owned())),
+                    param: attr(rtcp_fb, "subtype").map(|s| s.to_owned()),
+                })
+            })
+            .collect();
+
+        Some(JinglePayloadType {
+            id: attr(element, "id")?.parse().ok()?,
+            name: attr(element, "name")?.to_owned(),
+            clock_rate: attr(element, "clockrate")?.parse().ok()?,
+            channels: attr(element, "channels").and_then(|value| value.parse().ok()),
+            feedback,
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let channels = match self.channels {
+            Some(channels) => format!(r#" channels="{}""#, channels),
+            None => String::new(),
+        };
+
+        let feedback = self
+            .feedback
+            .iter()
+            .map(|feedback| {
+                let subtype = match &feedback.param {
+                    Some(param) => format!(r#" subtype="{}""#, param),
+                    None => String::new(),
+                };
+                format!(r#"<rtcp-fb xmlns="urn:xmpp:jingle:apps:rtp:rtcp-fb:0" type="{}"{}/>"#, feedback.kind, subtype)
+            })
+            .collect::<Vec<_>>()
+            .join("");
+
+        format!(
+            r#"<payload-type id="{}" name="{}" clockrate="{}"{}>{}</payload-type>"#,
+            self.id, self.name, self.clock_rate, channels, feedback,
+        )
+    }
+
+    fn to_offered_codec(&self) -> OfferedCodec {
+        OfferedCodec {
+            payload_type: self.id,
+            name: self.name.clone(),
+            clock_rate: self.clock_rate,
+            channels: self.channels,
+            feedback: self.feedback.clone(),
+        }
+    }
+}
+
+impl JingleDescription {
+    pub fn from_xml(element: &str) -> Option<JingleDescription> {
+        use std::str::FromStr;
+
+        let media = attr(element, "media")?;
+
+        Some(JingleDescription {
+            media: MediaType::from_str(media).unwrap_or(MediaType::Unknown(media.to_owned())),
+            payload_types: extract_elements(element, "payload-type")
+                .into_iter()
+                .filter_map(JinglePayloadType::from_xml)
+                .collect(),
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let payload_types = self.payload_types.iter().map(JinglePayloadType::to_xml).collect::<Vec<_>>().join("");
+
+        format!(
+            r#"<description xmlns="urn:xmpp:jingle:apps:rtp:1" media="{}">{}</description>"#,
+            self.media, payload_types,
+        )
+    }
+}
+
+impl JingleContent {
+    pub fn from_xml(element: &str) -> Option<JingleContent> {
+        Some(JingleContent {
+            name: attr(element, "name")?.to_owned(),
+            senders: attr(element, "senders").and_then(senders_to_direction).unwrap_or(Direction::SendRecv),
+            description: extract_elements(element, "description").into_iter().next().and_then(JingleDescription::from_xml)?,
+            transport: extract_elements(element, "transport").into_iter().next().and_then(JingleTransport::from_xml)?,
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        format!(
+            r#"<content name="{}" creator="responder" senders="{}">{}{}</content>"#,
+            self.name,
+            direction_to_senders(self.senders),
+            self.description.to_xml(),
+            self.transport.to_xml(),
+        )
+    }
+}
+
+fn senders_to_direction(value: &str) -> Option<Direction> {
+    match value {
+        "both" => Some(Direction::SendRecv),
+        "initiator" => Some(Direction::RecvOnly),
+        "responder" => Some(Direction::SendOnly),
+        "none" => Some(Direction::Inactive),
+        _ => None,
+    }
+}
+
+fn direction_to_senders(direction: Direction) -> &'static str {
+    match direction {
+        Direction::SendRecv => "both",
+        Direction::RecvOnly => "initiator",
+        Direction::SendOnly => "responder",
+        Direction::Inactive => "none",
+    }
+}
+
+impl JingleGroup {
+    pub fn from_xml(element: &str) -> Option<JingleGroup> {
+        Some(JingleGroup {
+            semantics: attr(element, "semantics")?.to_owned(),
+            contents: extract_elements(element, "content")
+                .into_iter()
+                .filter_map(|content| attr(content, "name").map(|name| name.to_owned()))
+                .collect(),
+        })
+    }
+
+    pub fn to_xml(&self) -> String {
+        let contents = self
+            .contents
+            .iter()
+            .map(|name| format!(r#"<content name="{}"/>"#, name))
+            .collect::<Vec<_>>()
+            .join("");
+
+        format!(
+            r#"<group xmlns="urn:xmpp:jingle:apps:grouping:0" semantics="{}">{}</group>"#,
+            self.semantics, contents,
+        )
+    }
+}
+
+/// Converts a set of Jingle `<content/>` elements (plus an optional bundle
+/// `<group/>`) into the internal [`Session`](crate::sdp::Session) the
+/// negotiation layer in [`jsep`](crate::sdp::jsep) understands.
+pub fn contents_to_session(contents: &[JingleContent], group: Option<&JingleGroup>) -> crate::sdp::Session {
+    let mut session = crate::sdp::Session::default();
+
+    for content in contents {
+        let transport = &content.transport;
+
+        if session.ice_ufrag.is_none() {
+            session.ice_ufrag = Some(transport.ufrag.clone());
+            session.ice_pwd = Some(transport.pwd.clone());
+        }
+
+        if let Some(fingerprint) = &transport.fingerprint {
+            session.fingerprint_hash = Some(fingerprint.hash.clone());
+            session.fingerprint_value = Some(fingerprint.value.clone());
+            session.setup = Some(fingerprint.setup.clone());
+        }
+
+        session.media_descriptions.push(crate::sdp::MediaDescription {
+            kind: content.description.media.clone(),
+            port: 9,
+            protocol: "UDP/TLS/RTP/SAVPF".to_owned(),
+            mid: Some(content.name.clone()),
+            direction: content.senders,
+            codecs: content.description.payload_types.iter().map(JinglePayloadType::to_offered_codec).collect(),
+        });
+    }
+
+    session.bundle_mids = match group {
+        Some(group) if group.semantics == "BUNDLE" => group.contents.clone(),
+        _ => Vec::new(),
+    };
+
+    session
+}
+
+/// Converts a negotiated answer back into Jingle `<content/>` elements (plus
+/// the bundle `<group/>`, when the session has any bundled mids), so this crate can
+/// act as the Jingle initiator as well as the responder.
+pub fn session_to_contents(session: &NegotiatedSession) -> (Vec<JingleContent>, Option<JingleGroup>) {
+    let contents = session
+        .media_descriptions
+        .iter()
+        .map(|media| JingleContent {
+            name: media.mid.clone().unwrap_or_else(|| media.kind.to_string()),
+            senders: media.direction,
+            description: JingleDescription {
+                media: media.kind.clone(),
+                payload_types: media
+                    .codecs
+                    .iter()
+                    .map(|codec| JinglePayloadType {
+                        id: codec.payload_type,
+                        name: codec.name.clone(),
+                        clock_rate: codec.clock_rate,
+                        channels: codec.channels,
+                        feedback: codec.feedback.clone(),
+                    })
+                    .collect(),
+            },
+            transport: JingleTransport {
+                ufrag: session.ice_ufrag.clone(),
+                pwd: session.ice_pwd.clone(),
+                candidates: Vec::new(),
+                fingerprint: Some(JingleFingerprint {
+                    hash: session.fingerprint_hash.clone(),
+                    value: session.fingerprint_value.clone(),
+                    setup: session.setup.clone(),
+                }),
+            },
+        })
+        .collect();
+
+    let group = if session.bundle_mids.is_empty() {
+        None
+    } else {
+        Some(JingleGroup {
+            semantics: "BUNDLE".to_owned(),
+            contents: session.bundle_mids.clone(),
+        })
+    };
+
+    (contents, group)
+}
@@ -1,32 +1,36 @@
 mod bridge;
+pub mod ice;
+pub mod jingle;
+pub mod logging;
+pub mod sdp;
+pub mod srtp;
+mod stun;
+pub mod turn;
 
-use parking_lot::{Mutex, const_mutex};
+use parking_lot::Once;
 
 // TODO: Figure out an error handling strategy once we have more errors.
 pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
-static INIT_MUTEX: Mutex<bool> = const_mutex(false);
+pub use sdp::EnumParseError;
 
-pub fn library_init() -> Result<()> {
-    let mut is_init = INIT_MUTEX.lock();
-
-    if *is_init {
-        return Ok(());
-    }
+static INIT: Once = Once::new();
 
-    *is_init = true;
+pub fn library_init() -> Result<()> {
+    let mut result = Ok(());
 
-    // TODO: Expose the logging config to consumers.
-    bridge::logger_enable_log(true);
-    bridge::logger_enable_debug(true);
-    bridge::logger_enable_ultra_debug(false);
+    INIT.call_once(|| {
+        result = (|| {
+            bridge::openssl_class_init()?;
 
-    bridge::openssl_class_init()?;
+            // It is unfortunate that this is global state.
+            bridge::dtls_connection_initialize()?;
 
-    // It is unfortunate that this is global state.
-    bridge::dtls_connection_initialize()?;
+            Ok(())
+        })();
+    });
 
-    Ok(())
+    result
 }
 
 pub enum DtlsConnectionHash {
@@ -110,4 +114,23 @@ impl RtpBundleTransport {
         let connection = self.0.add_ice_transport(username, &properties.0)?;
         Ok(RtpBundleTransportConnection(connection))
     }
+
+    /// Gathers host candidates for this transport's local port on every
+    /// non-loopback local interface, plus a server-reflexive candidate if `stun_server` is given
+    /// and a relay candidate if `turn_server` is given.
+    ///
+    /// Callers are expected to feed each returned candidate through their own
+    /// signaling channel, the same way `on_local_ice_candidate` would report
+    /// them if gathering were ever moved to the native side. When a relay
+    /// candidate is gathered, hang on to `GatheredCandidates::turn_allocation`
+    /// to install permissions for the remote peer and to refresh it before
+    /// its lifetime runs out.
+    pub fn gather_local_candidates(
+        &self,
+        component: u16,
+        stun_server: Option<std::net::SocketAddr>,
+        turn_server: Option<&turn::TurnServerConfig>,
+    ) -> Result<ice::GatheredCandidates> {
+        ice::gather_local_candidates(self.get_local_port(), component, stun_server, turn_server)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+//! Bridges C++ `media-server` log records into Rust.
+//!
+//! Previously logging was all-or-nothing: `logger_enable_log`,
+//! `logger_enable_debug` and `logger_enable_ultra_debug` just toggled stdout
+//! output on the native side. The native side now calls `dispatch_log_record`
+//! (declared in [`crate::bridge`]) for every record that passes its own
+//! threshold, set with [`set_level`]; register a [`LogSink`] with
+//! [`set_sink`] to receive them, e.g. to forward into `tracing`/`log` with
+//! per-level filtering.
+
+use parking_lot::{const_mutex, Mutex};
+
+pub use crate::bridge::LogLevel;
+use crate::bridge;
+
+/// Receives every C++ log record that passes the native threshold set via
+/// [`set_level`]. The crate does not forward into `tracing`/`log` itself, so
+/// consumers aren't forced into a particular logging framework.
+pub trait LogSink: Send + Sync {
+    fn on_log_record(&self, level: LogLevel, category: &str, message: &str);
+}
+
+static SINK: Mutex<Option<Box<dyn LogSink>>> = const_mutex(None);
+
+/// Registers the sink that receives log records, replacing any previous one.
+/// Safe to call more than once, from any thread.
+pub fn set_sink(sink: impl LogSink + 'static) {
+    *SINK.lock() = Some(Box::new(sink));
+}
+
+/// Sets the native logger's severity threshold, replacing the old
+/// `logger_enable_log`/`logger_enable_debug`/`logger_enable_ultra_debug`
+/// booleans.
+pub fn set_level(level: LogLevel) {
+    bridge::logger_set_level(level);
+}
+
+pub(crate) fn dispatch(level: LogLevel, category: &str, message: &str) {
+    if let Some(sink) = SINK.lock().as_deref() {
+        sink.on_log_record(level, category, message);
+    }
+}
@@ -0,0 +1,180 @@
+//! JSEP-style offer/answer negotiation.
+//!
+//! Takes a parsed SDP offer (see [`Session::parse`]) plus a local codec capability list and
+//! produces both an answer SDP string and a [`Properties`] bag ready to pass
+//! into [`RtpBundleTransport::add_ice_transport`](crate::RtpBundleTransport::add_ice_transport),
+//! so callers no longer have to scrape the offer by hand.
+
+use crate::sdp::{Direction, MediaDescription, MediaType, OfferedCodec, Session, SetupRole};
+use crate::{Properties, Result};
+
+/// A codec we are able to receive/send, used to filter the offered codecs down
+/// to the ones we can actually negotiate.
+#[derive(Debug, Clone)]
+pub struct LocalCodec {
+    pub name: String,
+    pub clock_rate: u32,
+    pub channels: Option<u8>,
+}
+
+impl LocalCodec {
+    fn matches(&self, offered: &OfferedCodec) -> bool {
+        self.name.eq_ignore_ascii_case(&offered.name)
+            && self.clock_rate == offered.clock_rate
+            && self.channels == offered.channels
+    }
+}
+
+/// One negotiated media section: the payload type numbers are always carried
+/// forward from the offer, as required by the answerer/offerer symmetry JSEP
+/// relies on.
+#[derive(Debug, Clone)]
+pub struct NegotiatedMediaDescription {
+    pub kind: MediaType,
+    pub mid: Option<String>,
+    pub direction: Direction,
+    pub codecs: Vec<OfferedCodec>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NegotiatedSession {
+    pub ice_ufrag: String,
+    pub ice_pwd: String,
+    pub setup: SetupRole,
+    pub fingerprint_hash: String,
+    pub fingerprint_value: String,
+    pub bundle_mids: Vec<String>,
+    pub media_descriptions: Vec<NegotiatedMediaDescription>,
+}
+
+/// Negotiates an answer for `offer`, keeping only the codecs present in
+/// `local_codecs`, and answering with `local_direction` intersected against
+/// what each offered `m=` section asked for.
+pub fn negotiate(offer: &Session, local_codecs: &[LocalCodec], local_direction: Direction) -> Result<NegotiatedSession> {
+    let ice_ufrag = offer.ice_ufrag.clone().ok_or("offer is missing ice-ufrag")?;
+    let ice_pwd = offer.ice_pwd.clone().ok_or("offer is missing ice-pwd")?;
+    let offered_setup = offer.setup.clone().ok_or("offer is missing a=setup")?;
+    let fingerprint_hash = offer.fingerprint_hash.clone().ok_or("offer is missing a=fingerprint")?;
+    let fingerprint_value = offer.fingerprint_value.clone().ok_or("offer is missing a=fingerprint")?;
+
+    let media_descriptions = offer
+        .media_descriptions
+        .iter()
+        .map(|media| negotiate_media(media, local_codecs, local_direction))
+        .collect();
+
+    Ok(NegotiatedSession {
+        ice_ufrag,
+        ice_pwd,
+        setup: offered_setup.answer(),
+        fingerprint_hash,
+        fingerprint_value,
+        bundle_mids: offer.bundle_mids.clone(),
+        media_descriptions,
+    })
+}
+
+fn negotiate_media(
+    media: &MediaDescription,
+    local_codecs: &[LocalCodec],
+    local_direction: Direction,
+) -> NegotiatedMediaDescription {
+    let codecs = media
+        .codecs
+        .iter()
+        .filter(|offered| local_codecs.iter().any(|local| local.matches(offered)))
+        .cloned()
+        .collect();
+
+    NegotiatedMediaDescription {
+        kind: media.kind.clone(),
+        mid: media.mid.clone(),
+        direction: Direction::negotiate(media.direction, local_direction),
+        codecs,
+    }
+}
+
+impl NegotiatedSession {
+    /// Builds the `Properties` bag expected by `add_ice_transport`.
+    ///
+    /// `srtp_protection_profiles` should come from
+    /// [`srtp::negotiate`](crate::srtp::negotiate), rather than being left as
+    /// `""` and handshaking with whatever the backend defaults to.
+    pub fn to_properties(&self, local_ufrag: &str, local_pwd: &str, srtp_protection_profiles: &str) -> Properties {
+        let properties = Properties::new();
+
+        properties.set_string("ice.localUsername", local_ufrag);
+        properties.set_string("ice.localPassword", local_pwd);
+        properties.set_string("ice.remoteUsername", &self.ice_ufrag);
+        properties.set_string("ice.remotePassword", &self.ice_pwd);
+        properties.set_string("dtls.setup", self.setup.as_ref());
+        properties.set_string("dtls.hash", &self.fingerprint_hash);
+        properties.set_string("dtls.fingerprint", &self.fingerprint_value);
+        properties.set_string("srtpProtectionProfiles", srtp_protection_profiles);
+        properties.set_bool("disableSTUNKeepAlive", false);
+
+        properties
+    }
+
+    /// Renders the negotiated answer as an SDP string.
+    pub fn to_sdp_string(&self, local_fingerprint_hash: &str, local_fingerprint_value: &str) -> String {
+        let mut sdp = String::new();
+
+        sdp.push_str("v=0\r\n");
+        sdp.push_str("o=- 0 0 IN IP4 0.0.0.0\r\n");
+        sdp.push_str("s=-\r\n");
+        sdp.push_str("t=0 0\r\n");
+
+        if !self.bundle_mids.is_empty() {
+            sdp.push_str(&format!("a=group:BUNDLE {}\r\n", self.bundle_mids.join(" ")));
+        }
+
+        for media in &self.media_descriptions {
+            let formats = media
+                .codecs
+                .iter()
+                .map(|codec| codec.payload_type.to_string())
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            sdp.push_str(&format!("m={} 9 UDP/TLS/RTP/SAVPF {}\r\n", media.kind, formats));
+            sdp.push_str("c=IN IP4 0.0.0.0\r\n");
+            sdp.push_str(&format!("a=ice-ufrag:{}\r\n", self.ice_ufrag));
+            sdp.push_str(&format!("a=ice-pwd:{}\r\n", self.ice_pwd));
+            sdp.push_str(&format!("a=setup:{}\r\n", self.setup));
+            sdp.push_str(&format!(
+                "a=fingerprint:{} {}\r\n",
+                local_fingerprint_hash, local_fingerprint_value
+            ));
+
+            if let Some(mid) = &media.mid {
+                sdp.push_str(&format!("a=mid:{}\r\n", mid));
+            }
+
+            sdp.push_str(&format!("a={}\r\n", media.direction));
+
+            for codec in &media.codecs {
+                let channels = match codec.channels {
+                    Some(channels) => format!("/{}", channels),
+                    None => String::new(),
+                };
+
+                sdp.push_str(&format!(
+                    "a=rtpmap:{} {}/{}{}\r\n",
+                    codec.payload_type, codec.name, codec.clock_rate, channels
+                ));
+
+                for feedback in &codec.feedback {
+                    let param = match &feedback.param {
+                        Some(param) => format!(" {}", param),
+                        None => String::new(),
+                    };
+
+                    sdp.push_str(&format!("a=rtcp-fb:{} {}{}\r\n", codec.payload_type, feedback.kind, param));
+                }
+            }
+        }
+
+        sdp
+    }
+}
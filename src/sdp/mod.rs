@@ -0,0 +1,299 @@
+//! Minimal SDP parsing, just enough to drive the JSEP-style negotiation in
+//! [`jsep`](crate::sdp::jsep) and fill in the `Properties` bag expected by
+//! [`RtpBundleTransport::add_ice_transport`](crate::RtpBundleTransport::add_ice_transport).
+//!
+//! This is deliberately not a general-purpose SDP library: it only keeps the
+//! handful of session/media attributes the negotiation layer cares about.
+
+use semantic_sdp_derive::SdpEnum;
+
+pub mod jsep;
+
+pub enum EnumParseError {
+    VariantNotFound,
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, SdpEnum)]
+pub enum MediaType {
+    #[sdp("audio")]
+    Audio,
+    #[sdp("video")]
+    Video,
+
+    #[sdp(default)]
+    Unknown(String),
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, SdpEnum)]
+pub enum SetupRole {
+    #[sdp("active")]
+    Active,
+    #[sdp("passive")]
+    Passive,
+    #[sdp("actpass")]
+    ActivePassive,
+}
+
+impl SetupRole {
+    /// The role we should offer in an answer when the offer specified `self`.
+    pub fn answer(&self) -> SetupRole {
+        match self {
+            SetupRole::Active => SetupRole::Passive,
+            SetupRole::Passive => SetupRole::Active,
+            SetupRole::ActivePassive => SetupRole::Active,
+        }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, SdpEnum)]
+pub enum Direction {
+    #[sdp("sendrecv")]
+    SendRecv,
+    #[sdp("sendonly")]
+    SendOnly,
+    #[sdp("recvonly")]
+    RecvOnly,
+    #[sdp("inactive")]
+    Inactive,
+}
+
+impl Direction {
+    fn as_bools(&self) -> (bool, bool) {
+        match self {
+            Direction::SendRecv => (true, true),
+            Direction::SendOnly => (true, false),
+            Direction::RecvOnly => (false, true),
+            Direction::Inactive => (false, false),
+        }
+    }
+
+    fn from_bools(send: bool, recv: bool) -> Direction {
+        match (send, recv) {
+            (true, true) => Direction::SendRecv,
+            (true, false) => Direction::SendOnly,
+            (false, true) => Direction::RecvOnly,
+            (false, false) => Direction::Inactive,
+        }
+    }
+
+    /// Intersects an offered direction with what we (the answerer) are willing to do.
+    pub fn negotiate(offer: Direction, answer_capability: Direction) -> Direction {
+        let (offer_send, offer_recv) = offer.as_bools();
+        let (our_send, our_recv) = answer_capability.as_bools();
+
+        Direction::from_bools(offer_send && our_send, offer_recv && our_recv)
+    }
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, SdpEnum)]
+pub enum RtcpFeedbackType {
+    #[sdp("nack")]
+    Nack,
+    #[sdp("ccm")]
+    Ccm,
+    #[sdp("goog-remb")]
+    GoogRemb,
+    #[sdp("transport-cc")]
+    TransportCc,
+
+    #[sdp(default)]
+    Unknown(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct RtcpFeedback {
+    pub kind: RtcpFeedbackType,
+    pub param: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OfferedCodec {
+    pub payload_type: u8,
+    pub name: String,
+    pub clock_rate: u32,
+    pub channels: Option<u8>,
+    pub feedback: Vec<RtcpFeedback>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaDescription {
+    pub kind: MediaType,
+    pub port: u16,
+    pub protocol: String,
+    pub mid: Option<String>,
+    pub direction: Direction,
+    pub codecs: Vec<OfferedCodec>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    pub ice_ufrag: Option<String>,
+    pub ice_pwd: Option<String>,
+    pub fingerprint_hash: Option<String>,
+    pub fingerprint_value: Option<String>,
+    pub setup: Option<SetupRole>,
+    pub bundle_mids: Vec<String>,
+    pub media_descriptions: Vec<MediaDescription>,
+}
+
+/// Splits a `\r\n`-or-`\n`-delimited SDP body into its `x=value` lines.
+fn lines(sdp: &str) -> impl Iterator<Item = (u8, &str)> {
+    sdp.lines().filter_map(|line| {
+        let line = line.trim_end_matches('\r');
+        let bytes = line.as_bytes();
+        if bytes.len() < 2 || bytes[1] != b'=' {
+            return None;
+        }
+        Some((bytes[0], &line[2..]))
+    })
+}
+
+fn parse_attribute(line: &str) -> (&str, Option<&str>) {
+    match line.find(':') {
+        Some(pos) => (&line[..pos], Some(&line[pos + 1..])),
+        None => (line, None),
+    }
+}
+
+impl Session {
+    /// Parses just enough of an SDP offer/answer to negotiate a single bundled
+    /// RTP transport: the session-level ICE/DTLS attributes, the BUNDLE group,
+    /// and one [`MediaDescription`] per `m=` line.
+    pub fn parse(sdp: &str) -> Result<Session, String> {
+        use std::str::FromStr;
+
+        let mut session = Session::default();
+        let mut current: Option<MediaDescription> = None;
+
+        for (kind, value) in lines(sdp) {
+            match kind {
+                b'm' => {
+                    if let Some(media) = current.take() {
+                        session.media_descriptions.push(media);
+                    }
+
+                    let mut fields = value.split(' ');
+                    let media_type = fields.next().ok_or("m= line missing media type")?;
+                    let port = fields.next().ok_or("m= line missing port")?;
+                    let protocol = fields.next().ok_or("m= line missing protocol")?;
+
+                    current = Some(MediaDescription {
+                        kind: MediaType::from_str(media_type).unwrap_or(MediaType::Unknown(media_type.to_owned())),
+                        port: port.parse().map_err(|_| "invalid port in m= line")?,
+                        protocol: protocol.to_owned(),
+                        mid: None,
+                        direction: Direction::SendRecv,
+                        codecs: Vec::new(),
+                    });
+                }
+                b'a' => {
+                    let (name, attribute_value) = parse_attribute(value);
+
+                    match name {
+                        "ice-ufrag" => session.ice_ufrag = attribute_value.map(|s| s.to_owned()),
+                        "ice-pwd" => session.ice_pwd = attribute_value.map(|s| s.to_owned()),
+                        "setup" => {
+                            if let Some(value) = attribute_value {
+                                session.setup = SetupRole::from_str(value).ok();
+                            }
+                        }
+                        "fingerprint" => {
+                            if let Some(value) = attribute_value {
+                                let mut parts = value.splitn(2, ' ');
+                                session.fingerprint_hash = parts.next().map(|s| s.to_owned());
+                                session.fingerprint_value = parts.next().map(|s| s.to_owned());
+                            }
+                        }
+                        "group" => {
+                            if let Some(value) = attribute_value {
+                                let mut fields = value.split(' ');
+                                if fields.next() == Some("BUNDLE") {
+                                    session.bundle_mids = fields.map(|s| s.to_owned()).collect();
+                                }
+                            }
+                        }
+                        "mid" => {
+                            if let Some(media) = current.as_mut() {
+                                media.mid = attribute_value.map(|s| s.to_owned());
+                            }
+                        }
+                        "sendrecv" | "sendonly" | "recvonly" | "inactive" => {
+                            if let Some(media) = current.as_mut() {
+                                media.direction = Direction::from_str(name).unwrap_or(Direction::SendRecv);
+                            }
+                        }
+                        "rtpmap" => {
+                            if let Some(media) = current.as_mut() {
+                                if let Some(codec) = parse_rtpmap(attribute_value.unwrap_or_default()) {
+                                    media.codecs.push(codec);
+                                }
+                            }
+                        }
+                        "rtcp-fb" => {
+                            if let Some(media) = current.as_mut() {
+                                if let Some(value) = attribute_value {
+                                    add_rtcp_feedback(media, value);
+                                }
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(media) = current.take() {
+            session.media_descriptions.push(media);
+        }
+
+        Ok(session)
+    }
+}
+
+fn parse_rtpmap(value: &str) -> Option<OfferedCodec> {
+    let mut fields = value.splitn(2, ' ');
+    let payload_type = fields.next()?.parse().ok()?;
+    let mut name_clock_channels = fields.next()?.split('/');
+    let name = name_clock_channels.next()?.to_owned();
+    let clock_rate = name_clock_channels.next()?.parse().ok()?;
+    let channels = name_clock_channels.next().and_then(|s| s.parse().ok());
+
+    Some(OfferedCodec {
+        payload_type,
+        name,
+        clock_rate,
+        channels,
+        feedback: Vec::new(),
+    })
+}
+
+fn add_rtcp_feedback(media: &mut MediaDescription, value: &str) {
+    use std::str::FromStr;
+
+    let mut fields = value.splitn(2, ' ');
+    let payload_type: Option<u8> = match fields.next() {
+        Some("*") | None => None,
+        Some(payload) => payload.parse().ok(),
+    };
+
+    let rest = fields.next().unwrap_or_default();
+    let mut rest_fields = rest.splitn(2, ' ');
+    let kind = rest_fields.next().unwrap_or_default();
+    let param = rest_fields.next().map(|s| s.to_owned());
+
+    let feedback = RtcpFeedback {
+        kind: RtcpFeedbackType::from_str(kind).unwrap_or(RtcpFeedbackType::Unknown(kind.to_owned())),
+        param,
+    };
+
+    for codec in &mut media.codecs {
+        if payload_type.is_none() || payload_type == Some(codec.payload_type) {
+            codec.feedback.push(feedback.clone());
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! SRTP crypto-suite ("protection profile") negotiation for DTLS-SRTP.
+//!
+//! The underlying `SRTPSession`/OpenSSL backend is configured via
+//! `SSL_CTX_set_tlsext_use_srtp`, which expects an ordered, colon-separated
+//! list of profile names like `SRTP_AEAD_AES_256_GCM:SRTP_AES128_CM_SHA1_80`
+//! rather than a single chosen value — this renders that list instead of the
+//! `""` every test currently sets for `srtpProtectionProfiles`.
+
+use semantic_sdp_derive::SdpEnum;
+
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, SdpEnum)]
+pub enum SrtpProtectionProfile {
+    #[sdp("SRTP_AES128_CM_SHA1_80")]
+    Aes128CmSha1_80,
+    #[sdp("SRTP_AES128_CM_SHA1_32")]
+    Aes128CmSha1_32,
+    #[sdp("SRTP_AEAD_AES_128_GCM")]
+    AeadAes128Gcm,
+    #[sdp("SRTP_AEAD_AES_256_GCM")]
+    AeadAes256Gcm,
+}
+
+/// The profiles the compiled srtp/OpenSSL backend actually supports: both
+/// the AES-ICM (`aes_icm_ossl.c`) and AES-GCM (`aes_gcm_ossl.c`) ciphers are
+/// built in (see `build.rs`), so all four profiles are available.
+pub const COMPILED_PROFILES: &[SrtpProtectionProfile] = &[
+    SrtpProtectionProfile::AeadAes256Gcm,
+    SrtpProtectionProfile::AeadAes128Gcm,
+    SrtpProtectionProfile::Aes128CmSha1_80,
+    SrtpProtectionProfile::Aes128CmSha1_32,
+];
+
+/// Filters `local_preference` down to the profiles that are both offered by
+/// the peer and supported by the compiled backend, preserving local
+/// preference order, and renders the result as the colon-separated string
+/// `SRTPSession` expects. Errors if there is no overlap at all, rather than
+/// letting the handshake silently fall back to defaults.
+pub fn negotiate(local_preference: &[SrtpProtectionProfile], peer_offered: &[SrtpProtectionProfile]) -> crate::Result<String> {
+    let profiles: Vec<&str> = local_preference
+        .iter()
+        .copied()
+        .filter(|profile| COMPILED_PROFILES.contains(profile) && peer_offered.contains(profile))
+        .map(|profile| profile.as_ref())
+        .collect();
+
+    if profiles.is_empty() {
+        return Err("no mutually supported SRTP protection profile".into());
+    }
+
+    Ok(profiles.join(":"))
+}
@@ -0,0 +1,125 @@
+//! A tiny RFC 5389 STUN client, just enough to send a Binding request and
+//! read back the XOR-MAPPED-ADDRESS. Used by ICE server-reflexive candidate
+//! gathering in [`crate::ice`], and as the base message encoding for the TURN
+//! client in [`crate::turn`].
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+pub(crate) const MAGIC_COOKIE: u32 = 0x2112_A442;
+const BINDING_REQUEST: u16 = 0x0001;
+const BINDING_SUCCESS_RESPONSE: u16 = 0x0101;
+const ATTR_XOR_MAPPED_ADDRESS: u16 = 0x0020;
+
+/// Builds a STUN Binding request with a random transaction ID, returning the
+/// message bytes alongside the transaction ID so the response can be matched.
+pub fn binding_request() -> ([u8; 20], [u8; 12]) {
+    use rand::RngCore;
+
+    let mut transaction_id = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut transaction_id);
+
+    let mut message = [0u8; 20];
+    message[0..2].copy_from_slice(&BINDING_REQUEST.to_be_bytes());
+    message[2..4].copy_from_slice(&0u16.to_be_bytes()); // message length, no attributes
+    message[4..8].copy_from_slice(&MAGIC_COOKIE.to_be_bytes());
+    message[8..20].copy_from_slice(&transaction_id);
+
+    (message, transaction_id)
+}
+
+/// Checks the message header (type + transaction id) and returns the body,
+/// i.e. the bytes after the 20-byte header. Used by both the Binding client
+/// here and the TURN client in [`crate::turn`].
+pub(crate) fn check_header<'a>(response: &'a [u8], expected_message_type: u16, expected_transaction_id: &[u8; 12]) -> crate::Result<&'a [u8]> {
+    if response.len() < 20 {
+        return Err("STUN response shorter than the message header".into());
+    }
+
+    let message_type = u16::from_be_bytes([response[0], response[1]]);
+    if message_type != expected_message_type {
+        return Err("unexpected STUN message type".into());
+    }
+
+    if &response[8..20] != expected_transaction_id {
+        return Err("STUN response transaction id did not match the request".into());
+    }
+
+    Ok(&response[20..])
+}
+
+/// Finds the first attribute of `attr_type` in a STUN message body (the bytes
+/// following the 20-byte header), honoring the 4-byte attribute padding.
+pub(crate) fn find_attribute(body: &[u8], attr_type: u16) -> Option<&[u8]> {
+    let mut offset = 0;
+    while offset + 4 <= body.len() {
+        let this_type = u16::from_be_bytes([body[offset], body[offset + 1]]);
+        let attr_len = u16::from_be_bytes([body[offset + 2], body[offset + 3]]) as usize;
+        let value_start = offset + 4;
+        let value_end = value_start + attr_len;
+
+        if value_end > body.len() {
+            break;
+        }
+
+        if this_type == attr_type {
+            return Some(&body[value_start..value_end]);
+        }
+
+        // Attributes are padded to a multiple of 4 bytes.
+        offset = value_start + ((attr_len + 3) & !3);
+    }
+
+    None
+}
+
+/// Parses a STUN Binding success response and extracts the XOR-MAPPED-ADDRESS.
+pub fn parse_xor_mapped_address(response: &[u8], expected_transaction_id: &[u8; 12]) -> crate::Result<SocketAddr> {
+    let body = check_header(response, BINDING_SUCCESS_RESPONSE, expected_transaction_id)?;
+
+    let value = find_attribute(body, ATTR_XOR_MAPPED_ADDRESS)
+        .ok_or("STUN response did not contain an XOR-MAPPED-ADDRESS attribute")?;
+
+    parse_xor_address_value(value)
+}
+
+pub(crate) fn parse_xor_address_value(value: &[u8]) -> crate::Result<SocketAddr> {
+    if value.len() < 4 {
+        return Err("XOR-MAPPED-ADDRESS attribute too short".into());
+    }
+
+    let family = value[1];
+    let xor_port = u16::from_be_bytes([value[2], value[3]]);
+    let port = xor_port ^ (MAGIC_COOKIE >> 16) as u16;
+
+    let ip = match family {
+        0x01 => {
+            if value.len() < 8 {
+                return Err("IPv4 XOR-MAPPED-ADDRESS attribute too short".into());
+            }
+
+            let cookie = MAGIC_COOKIE.to_be_bytes();
+            let octets = [
+                value[4] ^ cookie[0],
+                value[5] ^ cookie[1],
+                value[6] ^ cookie[2],
+                value[7] ^ cookie[3],
+            ];
+
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        0x02 => {
+            if value.len() < 20 {
+                return Err("IPv6 XOR-MAPPED-ADDRESS attribute too short".into());
+            }
+
+            // Full XOR mask is the magic cookie followed by the transaction ID,
+            // but we don't thread the transaction ID through here, so IPv6
+            // server-reflexive candidates aren't supported yet.
+            let _ = Ipv6Addr::UNSPECIFIED;
+            return Err("IPv6 XOR-MAPPED-ADDRESS is not supported".into());
+        }
+        _ => return Err("unknown address family in XOR-MAPPED-ADDRESS attribute".into()),
+    };
+
+    Ok(SocketAddr::new(ip, port))
+}